@@ -1,4 +1,18 @@
-#[derive(Debug, Default, Clone, Copy)]
+use std::fmt;
+
+use crate::keywords::{self, KeywordClass};
+use crate::number::NumberLiteral;
+
+mod nodes;
+
+pub use nodes::*;
+
+/// The name an identifier stands for, its text without the `r#` of a raw identifier
+pub fn identifier_name(text: &str) -> &str {
+    text.strip_prefix("r#").unwrap_or(text)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
@@ -30,17 +44,25 @@ impl From<char> for StrDenoter {
 }
 
 //#[allow(dead_code)]
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TokenKind {
     // Identifiers
     Identifier,
+    Underscore, // `_` on its own
 
     // Whitespace
     Whitespace,
     Newline,
 
+    // Comments
+    LineComment,     // `// ...`
+    BlockComment,    // `/* ... */`, may be nested
+    DocLineComment,  // `/// ...`
+    DocBlockComment, // `/** ... */`
+
     // Values
-    ValueNumber, // Can hold both integers and floats
+    ValueInteger, // `42`, `0xFF`, `0b1010`, `10u8`
+    ValueFloat,   // `1.5`, `1e-3`, `3.0f32`
     // ValueBool,
     ValueString,
     ValueChar,
@@ -104,7 +126,7 @@ pub enum TokenKind {
     KWordFalse,
     //KwordStartPoint,
     KwordIf,
-    KwordVar,
+    KwordLet,
     KwordNew,
     KwordThrow,
     KwordFor,
@@ -147,18 +169,114 @@ pub enum TokenKind {
     KwordFinal,
     KwordInline,
     KwordDelete,
+    KwordNull,
+    KwordIn,
+    KwordYield,
+    KwordAsync,
+    KwordThis,
+    KwordSelf,
     //KwordEndingPoint,
 
     // Special markers
+    Error, // Bytes that could not be lexed, see `Lexer::errors`
     Eof,
     #[default]
     Unknown,
 }
 
+impl TokenKind {
+    /// Whether this is a keyword that is only one in some places, and a name everywhere else
+    pub fn is_contextual_keyword(self) -> bool {
+        keywords::class(self) == Some(KeywordClass::Contextual)
+    }
+
+    /// The text of tokens that are always spelled the same way, like `{` or `func`
+    pub fn text(self) -> Option<&'static str> {
+        use TokenKind::*;
+
+        let text = match self {
+            Underscore => "_",
+            SymAt => "@",
+            SymDot => ".",
+            SymHash => "#",
+            SymComma => ",",
+            SymColon => ":",
+            SymColcol => "::",
+            SymQuestion => "?",
+            SymSemiColon => ";",
+            BracketLcurly => "{",
+            BracketRcurly => "}",
+            BracketLparent => "(",
+            BracketRparent => ")",
+            BracketLsquared => "[",
+            BracketRsquared => "]",
+            OpMul => "*",
+            OpMod => "%",
+            OpDiv => "/",
+            OpPlus => "+",
+            OpMinus => "-",
+            OpMuleq => "*=",
+            OpDiveq => "/=",
+            OpModEq => "%=",
+            OpPluseq => "+=",
+            OpMinuseq => "-=",
+            OpGt => ">",
+            OpLt => "<",
+            OpArrow => "->",
+            OpEqeq => "==",
+            OpGteq => ">=",
+            OpLteq => "<=",
+            OpNoteq => "!=",
+            OpEq => "=",
+            OpNot => "!",
+            OpAnd => "&&",
+            OpOr => "||",
+            OpBitNot => "~",
+            OpBitOr => "|",
+            OpBitAnd => "&",
+            OpBitXor => "^",
+            OpBitOrEq => "|=",
+            OpBitRshift => ">>",
+            OpBitLshift => "<<",
+            OpBitAndEq => "&=",
+            OpBitXorEq => "^=",
+            OpBitRshiftEq => ">>=",
+            OpBitLshiftEq => "<<=",
+            _ => return keywords::text(self),
+        };
+        Some(text)
+    }
+}
+
+/// How a token is named in diagnostics, like ``expected `{` `` or `expected identifier`
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(text) = self.text() {
+            return write!(f, "`{}`", text);
+        }
+
+        let name = match self {
+            TokenKind::Identifier => "identifier",
+            TokenKind::Whitespace => "whitespace",
+            TokenKind::Newline => "newline",
+            TokenKind::LineComment | TokenKind::BlockComment => "comment",
+            TokenKind::DocLineComment | TokenKind::DocBlockComment => "doc comment",
+            TokenKind::ValueInteger => "integer literal",
+            TokenKind::ValueFloat => "float literal",
+            TokenKind::ValueString => "string literal",
+            TokenKind::ValueChar => "character literal",
+            TokenKind::Error => "invalid token",
+            TokenKind::Eof => "end of file",
+            _ => "unknown token",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// Representing a token in the source code
 ///
 /// To get the actual value of the token, you can access its [`Span`] field and use it to get a slice of the source code
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Token {
     pub kind: TokenKind,
     pub span: Span,
@@ -171,4 +289,169 @@ impl Token {
             span: Span { start: 0, end: 0 },
         }
     }
+
+    /// Returns the documentation text of a doc comment token, with the comment markers stripped
+    ///
+    /// * `///` comments lose the `///` and a single following space
+    /// * `/** */` comments lose the delimiters, and every line loses its leading ` * ` decoration
+    ///
+    /// Returns [`None`] if the token is not a doc comment.
+    /// `source` must be the text the token was lexed from.
+    pub fn doc_text(&self, source: &str) -> Option<String> {
+        let text = &source[self.span.start..self.span.end];
+
+        match self.kind {
+            TokenKind::DocLineComment => {
+                let text = &text[3..];
+                Some(text.strip_prefix(' ').unwrap_or(text).to_string())
+            }
+            TokenKind::DocBlockComment => {
+                let text = &text[3..];
+                let text = text.strip_suffix("*/").unwrap_or(text);
+
+                let lines = text
+                    .lines()
+                    .map(|line| {
+                        let line = line.trim_start();
+                        let line = line.strip_prefix('*').unwrap_or(line);
+                        line.strip_prefix(' ').unwrap_or(line).trim_end()
+                    })
+                    .collect::<Vec<_>>();
+
+                // Drop the blank lines left over from `/**` and `*/` sitting on their own lines
+                let first = lines
+                    .iter()
+                    .position(|l| !l.is_empty())
+                    .unwrap_or(lines.len());
+                let last = lines
+                    .iter()
+                    .rposition(|l| !l.is_empty())
+                    .map_or(first, |i| i + 1);
+
+                Some(lines[first..last].join("\n"))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the cooked value of a string or char literal, with its quotes removed and its
+    /// escape sequences replaced by the characters they stand for
+    ///
+    /// Returns [`None`] if the token is not a [`TokenKind::ValueString`] or [`TokenKind::ValueChar`].
+    /// `source` must be the text the token was lexed from.
+    pub fn unescape(&self, source: &str) -> Option<String> {
+        let quote = match self.kind {
+            TokenKind::ValueString => StrDenoter::DoubleQuote,
+            TokenKind::ValueChar => StrDenoter::SingleQuote,
+            _ => return None,
+        };
+
+        let text = &source[self.span.start + 1..self.span.end];
+        // An unterminated literal has no closing quote to strip, and a quote that is preceded by
+        // an odd number of backslashes is escaped rather than closing
+        let text = match text.strip_suffix(char::from(quote)) {
+            Some(inner) if inner.bytes().rev().take_while(|b| *b == b'\\').count() % 2 == 0 => {
+                inner
+            }
+            _ => text,
+        };
+
+        Some(crate::escape::unescape(text))
+    }
+
+    /// Evaluates a [`TokenKind::ValueInteger`] or [`TokenKind::ValueFloat`] token, see
+    /// [`crate::number::evaluate`]
+    ///
+    /// Returns [`None`] for other tokens, and for malformed number literals.
+    /// `source` must be the text the token was lexed from.
+    pub fn number_value(&self, source: &str) -> Option<NumberLiteral> {
+        match self.kind {
+            TokenKind::ValueInteger | TokenKind::ValueFloat => {
+                crate::number::evaluate(&source[self.span.start..self.span.end])
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn doc_token(kind: TokenKind, source: &str) -> Token {
+        Token {
+            kind,
+            span: Span {
+                start: 0,
+                end: source.len(),
+            },
+        }
+    }
+
+    #[test]
+    fn doc_text_line() {
+        let src = "/// Adds two numbers";
+        let tok = doc_token(TokenKind::DocLineComment, src);
+        assert_eq!(tok.doc_text(src).as_deref(), Some("Adds two numbers"));
+    }
+
+    #[test]
+    fn doc_text_block() {
+        let src = "/**\n * Adds two numbers\n *\n *   together\n */";
+        let tok = doc_token(TokenKind::DocBlockComment, src);
+        assert_eq!(
+            tok.doc_text(src).as_deref(),
+            Some("Adds two numbers\n\n  together")
+        );
+
+        let src = "/** one line */";
+        let tok = doc_token(TokenKind::DocBlockComment, src);
+        assert_eq!(tok.doc_text(src).as_deref(), Some("one line"));
+    }
+
+    #[test]
+    fn unescape_literals() {
+        let src = r#""tab\there\u{21}""#;
+        let tok = doc_token(TokenKind::ValueString, src);
+        assert_eq!(tok.unescape(src).as_deref(), Some("tab\there!"));
+
+        let src = r"'\''";
+        let tok = doc_token(TokenKind::ValueChar, src);
+        assert_eq!(tok.unescape(src).as_deref(), Some("'"));
+
+        let src = "\"unterminated";
+        let tok = doc_token(TokenKind::ValueString, src);
+        assert_eq!(tok.unescape(src).as_deref(), Some("unterminated"));
+
+        let src = "abc";
+        let tok = doc_token(TokenKind::Identifier, src);
+        assert_eq!(tok.unescape(src), None);
+    }
+
+    #[test]
+    fn number_value() {
+        let src = "0xFF";
+        let tok = doc_token(TokenKind::ValueInteger, src);
+        let literal = tok.number_value(src).unwrap();
+        assert_eq!(literal.value.to_string(), "255");
+
+        let tok = doc_token(TokenKind::Identifier, src);
+        assert_eq!(tok.number_value(src), None);
+    }
+
+    #[test]
+    fn doc_text_not_a_doc_comment() {
+        let src = "// plain";
+        let tok = doc_token(TokenKind::LineComment, src);
+        assert_eq!(tok.doc_text(src), None);
+    }
+
+    #[test]
+    fn token_kind_display() {
+        assert_eq!(TokenKind::BracketLcurly.to_string(), "`{`");
+        assert_eq!(TokenKind::KwordFunc.to_string(), "`func`");
+        assert_eq!(TokenKind::Identifier.to_string(), "identifier");
+        assert_eq!(TokenKind::Underscore.to_string(), "`_`");
+        assert_eq!(TokenKind::Eof.to_string(), "end of file");
+    }
 }
@@ -0,0 +1,1076 @@
+//! Typed views of the nodes in the concrete syntax tree
+//!
+//! Each struct wraps a [`SyntaxNode`] of the [`SyntaxKind`] with the same name, and its
+//! accessors find the children the parser put there. They all return [`Option`] or iterators,
+//! since half typed code is missing parts all the time.
+
+use crate::ast::{identifier_name, TokenKind};
+use crate::lexer::LexerOptions;
+use crate::parser;
+use crate::syntax_kind::SyntaxKind;
+use crate::syntax_node::{SyntaxNode, SyntaxToken};
+use crate::text_edit::TextEdit;
+
+/// A typed view of a [`SyntaxNode`]
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+
+    fn cast(syntax: SyntaxNode) -> Option<Self>;
+
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+fn child<N: AstNode>(parent: &SyntaxNode) -> Option<N> {
+    parent.children().find_map(N::cast)
+}
+
+fn children<N: AstNode>(parent: &SyntaxNode) -> impl Iterator<Item = N> {
+    parent.children().filter_map(N::cast)
+}
+
+fn token(parent: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    parent
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind() == kind)
+}
+
+/// The tokens of a node that are not whitespace or comments
+fn significant_tokens(parent: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> {
+    parent
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| !token.kind().is_trivia())
+}
+
+macro_rules! ast_nodes {
+    ($($(#[$attr:meta])* $name:ident),* $(,)?) => {$(
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $name {
+            syntax: SyntaxNode,
+        }
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                kind == SyntaxKind::$name
+            }
+
+            fn cast(syntax: SyntaxNode) -> Option<Self> {
+                Self::can_cast(syntax.kind()).then_some($name { syntax })
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.syntax
+            }
+        }
+    )*};
+}
+
+macro_rules! ast_enums {
+    ($($(#[$attr:meta])* $name:ident { $($variant:ident),* $(,)? })*) => {$(
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant($variant),)*
+        }
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                matches!(kind, $(SyntaxKind::$variant)|*)
+            }
+
+            fn cast(syntax: SyntaxNode) -> Option<Self> {
+                let node = match syntax.kind() {
+                    $(SyntaxKind::$variant => $name::$variant($variant { syntax }),)*
+                    _ => return None,
+                };
+                Some(node)
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                match self {
+                    $($name::$variant(node) => &node.syntax,)*
+                }
+            }
+        }
+    )*};
+}
+
+ast_nodes! {
+    SourceFile,
+
+    // Declarations
+    FuncDecl, ClassDecl, StructDecl, EnumDecl, InterfaceDecl, NamespaceDecl, TypeAlias,
+    ImportDecl, ExternDecl, MacroDecl, VarDecl, FieldDecl,
+
+    // Parts of declarations
+    ModifierList, Name, Path, ParamList, Param, RetType, ItemList, VariantList, EnumVariant,
+    ExtendsClause, ImplementsClause, ImportAlias, TokenTree, GenericParamList, GenericParam,
+    GenericArgList,
+
+    // Types
+    PathType, PointerType, RefType, ArrayType,
+
+    // Statements
+    Block, IfStmt, WhileStmt, DoWhileStmt, ForStmt, SwitchStmt, CaseClause, DefaultClause,
+    BreakStmt, ContinueStmt, ReturnStmt, ThrowStmt, TryStmt, CatchClause, UnsafeBlock, ExprStmt,
+
+    // Expressions
+    Literal, PathExpr, BinaryExpr, PrefixExpr, TernaryExpr, CallExpr, IndexExpr, MemberExpr,
+    CastExpr, NewExpr, ParenExpr, ArgList,
+}
+
+ast_enums! {
+    /// A declaration
+    Item {
+        FuncDecl, ClassDecl, StructDecl, EnumDecl, InterfaceDecl, NamespaceDecl, TypeAlias,
+        ImportDecl, ExternDecl, MacroDecl, VarDecl, FieldDecl,
+    }
+
+    Type {
+        PathType, PointerType, RefType, ArrayType,
+    }
+
+    Expr {
+        Literal, PathExpr, BinaryExpr, PrefixExpr, TernaryExpr, CallExpr, IndexExpr, MemberExpr,
+        CastExpr, NewExpr, ParenExpr,
+    }
+
+    /// A clause of a `switch`
+    SwitchClause {
+        CaseClause, DefaultClause,
+    }
+}
+
+/// A statement, declarations can be nested in blocks too
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Stmt {
+    VarDecl(VarDecl),
+    ExprStmt(ExprStmt),
+    Block(Block),
+    IfStmt(IfStmt),
+    WhileStmt(WhileStmt),
+    DoWhileStmt(DoWhileStmt),
+    ForStmt(ForStmt),
+    SwitchStmt(SwitchStmt),
+    BreakStmt(BreakStmt),
+    ContinueStmt(ContinueStmt),
+    ReturnStmt(ReturnStmt),
+    ThrowStmt(ThrowStmt),
+    TryStmt(TryStmt),
+    UnsafeBlock(UnsafeBlock),
+    Item(Item),
+}
+
+impl AstNode for Stmt {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        matches!(
+            kind,
+            SyntaxKind::ExprStmt
+                | SyntaxKind::Block
+                | SyntaxKind::IfStmt
+                | SyntaxKind::WhileStmt
+                | SyntaxKind::DoWhileStmt
+                | SyntaxKind::ForStmt
+                | SyntaxKind::SwitchStmt
+                | SyntaxKind::BreakStmt
+                | SyntaxKind::ContinueStmt
+                | SyntaxKind::ReturnStmt
+                | SyntaxKind::ThrowStmt
+                | SyntaxKind::TryStmt
+                | SyntaxKind::UnsafeBlock
+        ) || Item::can_cast(kind)
+    }
+
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        let stmt = match syntax.kind() {
+            SyntaxKind::VarDecl => Stmt::VarDecl(VarDecl { syntax }),
+            SyntaxKind::ExprStmt => Stmt::ExprStmt(ExprStmt { syntax }),
+            SyntaxKind::Block => Stmt::Block(Block { syntax }),
+            SyntaxKind::IfStmt => Stmt::IfStmt(IfStmt { syntax }),
+            SyntaxKind::WhileStmt => Stmt::WhileStmt(WhileStmt { syntax }),
+            SyntaxKind::DoWhileStmt => Stmt::DoWhileStmt(DoWhileStmt { syntax }),
+            SyntaxKind::ForStmt => Stmt::ForStmt(ForStmt { syntax }),
+            SyntaxKind::SwitchStmt => Stmt::SwitchStmt(SwitchStmt { syntax }),
+            SyntaxKind::BreakStmt => Stmt::BreakStmt(BreakStmt { syntax }),
+            SyntaxKind::ContinueStmt => Stmt::ContinueStmt(ContinueStmt { syntax }),
+            SyntaxKind::ReturnStmt => Stmt::ReturnStmt(ReturnStmt { syntax }),
+            SyntaxKind::ThrowStmt => Stmt::ThrowStmt(ThrowStmt { syntax }),
+            SyntaxKind::TryStmt => Stmt::TryStmt(TryStmt { syntax }),
+            SyntaxKind::UnsafeBlock => Stmt::UnsafeBlock(UnsafeBlock { syntax }),
+            _ => Stmt::Item(Item::cast(syntax)?),
+        };
+        Some(stmt)
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Stmt::VarDecl(node) => &node.syntax,
+            Stmt::ExprStmt(node) => &node.syntax,
+            Stmt::Block(node) => &node.syntax,
+            Stmt::IfStmt(node) => &node.syntax,
+            Stmt::WhileStmt(node) => &node.syntax,
+            Stmt::DoWhileStmt(node) => &node.syntax,
+            Stmt::ForStmt(node) => &node.syntax,
+            Stmt::SwitchStmt(node) => &node.syntax,
+            Stmt::BreakStmt(node) => &node.syntax,
+            Stmt::ContinueStmt(node) => &node.syntax,
+            Stmt::ReturnStmt(node) => &node.syntax,
+            Stmt::ThrowStmt(node) => &node.syntax,
+            Stmt::TryStmt(node) => &node.syntax,
+            Stmt::UnsafeBlock(node) => &node.syntax,
+            Stmt::Item(node) => node.syntax(),
+        }
+    }
+}
+
+/// Nodes that are declared with a [`Name`]
+pub trait HasName: AstNode {
+    fn name(&self) -> Option<Name> {
+        child(self.syntax())
+    }
+}
+
+impl HasName for FuncDecl {}
+impl HasName for ClassDecl {}
+impl HasName for StructDecl {}
+impl HasName for EnumDecl {}
+impl HasName for InterfaceDecl {}
+impl HasName for TypeAlias {}
+impl HasName for MacroDecl {}
+impl HasName for VarDecl {}
+impl HasName for FieldDecl {}
+impl HasName for Param {}
+impl HasName for EnumVariant {}
+impl HasName for ImportAlias {}
+impl HasName for MemberExpr {}
+impl HasName for GenericParam {}
+
+/// Declarations that can have modifiers like `public` or `static`
+pub trait HasModifiers: AstNode {
+    fn modifier_list(&self) -> Option<ModifierList> {
+        child(self.syntax())
+    }
+
+    fn has_modifier(&self, kind: TokenKind) -> bool {
+        self.modifier_list()
+            .is_some_and(|list| list.modifiers().any(|token| token.kind() == kind.into()))
+    }
+}
+
+impl HasModifiers for FuncDecl {}
+impl HasModifiers for ClassDecl {}
+impl HasModifiers for StructDecl {}
+impl HasModifiers for EnumDecl {}
+impl HasModifiers for InterfaceDecl {}
+impl HasModifiers for NamespaceDecl {}
+impl HasModifiers for TypeAlias {}
+impl HasModifiers for ImportDecl {}
+impl HasModifiers for ExternDecl {}
+impl HasModifiers for MacroDecl {}
+impl HasModifiers for VarDecl {}
+impl HasModifiers for FieldDecl {}
+
+/// Declarations that can take generic parameters, like the `<T>` of `class Box<T>`
+pub trait HasGenericParams: AstNode {
+    fn generic_param_list(&self) -> Option<GenericParamList> {
+        child(self.syntax())
+    }
+
+    fn generic_params(&self) -> impl Iterator<Item = GenericParam> {
+        self.generic_param_list()
+            .into_iter()
+            .flat_map(|list| list.params())
+    }
+}
+
+impl HasGenericParams for FuncDecl {}
+impl HasGenericParams for ClassDecl {}
+impl HasGenericParams for StructDecl {}
+impl HasGenericParams for EnumDecl {}
+impl HasGenericParams for InterfaceDecl {}
+impl HasGenericParams for TypeAlias {}
+
+impl SourceFile {
+    pub fn items(&self) -> impl Iterator<Item = Item> {
+        children(&self.syntax)
+    }
+
+    /// The tree of the text after `edit`, reparsing only the `{ ... }` block the edit falls in
+    /// when its braces still match up
+    ///
+    /// `options` have to be the ones this tree was parsed with, like its edition. A tree doesn't
+    /// keep its errors, [`crate::parser::Parse::reparse`] updates those along with it.
+    pub fn reparse(&self, edit: &TextEdit, options: LexerOptions) -> SourceFile {
+        SourceFile {
+            syntax: SyntaxNode::new_root(parser::reparse(&self.syntax, edit, options)),
+        }
+    }
+}
+
+impl ModifierList {
+    pub fn modifiers(&self) -> impl Iterator<Item = SyntaxToken> {
+        significant_tokens(&self.syntax)
+    }
+}
+
+impl Name {
+    /// The name without any whitespace or comments, `operator==` for operators and `class` for
+    /// the raw identifier `r#class`
+    pub fn text(&self) -> String {
+        significant_tokens(&self.syntax)
+            .map(|token| identifier_name(token.text()).to_string())
+            .collect()
+    }
+
+    pub fn ident_token(&self) -> Option<SyntaxToken> {
+        token(&self.syntax, SyntaxKind::Identifier)
+    }
+}
+
+impl Path {
+    /// The names between the `::`s
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        significant_tokens(&self.syntax).filter(|token| token.kind().is_path_segment())
+    }
+
+    /// The generic arguments in the path, each one right after the name it belongs to
+    pub fn generic_arg_lists(&self) -> impl Iterator<Item = GenericArgList> {
+        children(&self.syntax)
+    }
+
+    /// The path without any whitespace, comments, generic arguments or `r#`s
+    pub fn text(&self) -> String {
+        significant_tokens(&self.syntax)
+            .map(|token| identifier_name(token.text()).to_string())
+            .collect()
+    }
+}
+
+impl GenericParamList {
+    pub fn params(&self) -> impl Iterator<Item = GenericParam> {
+        children(&self.syntax)
+    }
+}
+
+impl GenericArgList {
+    pub fn args(&self) -> impl Iterator<Item = Type> {
+        children(&self.syntax)
+    }
+}
+
+impl FuncDecl {
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.syntax)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = Param> {
+        self.param_list().into_iter().flat_map(|list| list.params())
+    }
+
+    pub fn ret_type(&self) -> Option<RetType> {
+        child(&self.syntax)
+    }
+
+    /// The body, [`None`] for declarations ending in `;`
+    pub fn body(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+
+    /// Whether this overloads an operator, like `func operator +`
+    pub fn is_operator(&self) -> bool {
+        self.name()
+            .is_some_and(|name| token(&name.syntax, SyntaxKind::KwordOperator).is_some())
+    }
+}
+
+impl ParamList {
+    pub fn params(&self) -> impl Iterator<Item = Param> {
+        children(&self.syntax)
+    }
+}
+
+impl Param {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn default_value(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl RetType {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl ClassDecl {
+    pub fn extends_clause(&self) -> Option<ExtendsClause> {
+        child(&self.syntax)
+    }
+
+    pub fn implements_clause(&self) -> Option<ImplementsClause> {
+        child(&self.syntax)
+    }
+
+    pub fn item_list(&self) -> Option<ItemList> {
+        child(&self.syntax)
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = Item> {
+        self.item_list().into_iter().flat_map(|list| list.items())
+    }
+}
+
+impl StructDecl {
+    pub fn item_list(&self) -> Option<ItemList> {
+        child(&self.syntax)
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = Item> {
+        self.item_list().into_iter().flat_map(|list| list.items())
+    }
+}
+
+impl InterfaceDecl {
+    pub fn extends_clause(&self) -> Option<ExtendsClause> {
+        child(&self.syntax)
+    }
+
+    pub fn item_list(&self) -> Option<ItemList> {
+        child(&self.syntax)
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = Item> {
+        self.item_list().into_iter().flat_map(|list| list.items())
+    }
+}
+
+impl ExtendsClause {
+    pub fn types(&self) -> impl Iterator<Item = Type> {
+        children(&self.syntax)
+    }
+}
+
+impl ImplementsClause {
+    pub fn types(&self) -> impl Iterator<Item = Type> {
+        children(&self.syntax)
+    }
+}
+
+impl ItemList {
+    pub fn items(&self) -> impl Iterator<Item = Item> {
+        children(&self.syntax)
+    }
+}
+
+impl FieldDecl {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl EnumDecl {
+    pub fn variant_list(&self) -> Option<VariantList> {
+        child(&self.syntax)
+    }
+
+    pub fn variants(&self) -> impl Iterator<Item = EnumVariant> {
+        self.variant_list()
+            .into_iter()
+            .flat_map(|list| list.variants())
+    }
+}
+
+impl VariantList {
+    pub fn variants(&self) -> impl Iterator<Item = EnumVariant> {
+        children(&self.syntax)
+    }
+}
+
+impl EnumVariant {
+    /// The types in parentheses after the name, like the `f64` of `Circle(f64)`
+    pub fn fields(&self) -> impl Iterator<Item = Type> {
+        children(&self.syntax)
+    }
+
+    /// The value after `=`
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl NamespaceDecl {
+    pub fn path(&self) -> Option<Path> {
+        child(&self.syntax)
+    }
+
+    pub fn item_list(&self) -> Option<ItemList> {
+        child(&self.syntax)
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = Item> {
+        self.item_list().into_iter().flat_map(|list| list.items())
+    }
+}
+
+impl TypeAlias {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl ImportDecl {
+    pub fn path(&self) -> Option<Path> {
+        child(&self.syntax)
+    }
+
+    pub fn alias(&self) -> Option<ImportAlias> {
+        child(&self.syntax)
+    }
+}
+
+impl ExternDecl {
+    /// The string naming the ABI, like `"C"`
+    pub fn abi(&self) -> Option<SyntaxToken> {
+        token(&self.syntax, SyntaxKind::ValueString)
+    }
+
+    /// The declarations, either the single one after the ABI or all of the ones in its braces
+    pub fn items(&self) -> impl Iterator<Item = Item> {
+        let list = child::<ItemList>(&self.syntax);
+        let single = match list {
+            Some(_) => None,
+            None => child::<Item>(&self.syntax),
+        };
+        list.into_iter().flat_map(|list| list.items()).chain(single)
+    }
+}
+
+impl MacroDecl {
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.syntax)
+    }
+
+    pub fn body(&self) -> Option<TokenTree> {
+        child(&self.syntax)
+    }
+}
+
+impl VarDecl {
+    /// The `let`, `const` or `mut` that starts the declaration
+    pub fn keyword(&self) -> Option<SyntaxToken> {
+        significant_tokens(&self.syntax).next()
+    }
+
+    pub fn is_const(&self) -> bool {
+        token(&self.syntax, SyntaxKind::KwordConst).is_some()
+    }
+
+    pub fn is_mut(&self) -> bool {
+        token(&self.syntax, SyntaxKind::KwordMutable).is_some()
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn initializer(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl PathType {
+    pub fn path(&self) -> Option<Path> {
+        child(&self.syntax)
+    }
+}
+
+impl PointerType {
+    pub fn pointee(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn is_const(&self) -> bool {
+        token(&self.syntax, SyntaxKind::KwordConst).is_some()
+    }
+
+    pub fn is_mut(&self) -> bool {
+        token(&self.syntax, SyntaxKind::KwordMutable).is_some()
+    }
+}
+
+impl RefType {
+    pub fn referent(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn is_mut(&self) -> bool {
+        token(&self.syntax, SyntaxKind::KwordMutable).is_some()
+    }
+}
+
+impl ArrayType {
+    pub fn element(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl Block {
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.syntax)
+    }
+}
+
+/// What comes after the `else` of an [`IfStmt`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ElseBranch {
+    If(IfStmt),
+    Block(Block),
+}
+
+impl IfStmt {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+
+    pub fn then_branch(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+
+    pub fn else_branch(&self) -> Option<ElseBranch> {
+        if let Some(if_stmt) = child(&self.syntax) {
+            return Some(ElseBranch::If(if_stmt));
+        }
+        children(&self.syntax).nth(1).map(ElseBranch::Block)
+    }
+}
+
+impl WhileStmt {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+}
+
+impl DoWhileStmt {
+    pub fn body(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl ForStmt {
+    /// The statement before the first `;`, either a [`VarDecl`] or an [`ExprStmt`]
+    pub fn initializer(&self) -> Option<Stmt> {
+        child(&self.syntax).filter(|stmt| matches!(stmt, Stmt::VarDecl(_) | Stmt::ExprStmt(_)))
+    }
+
+    pub fn condition(&self) -> Option<Expr> {
+        self.header_exprs().0
+    }
+
+    /// The expression after the second `;`, like the `i += 1` of `for (...; ...; i += 1)`
+    pub fn step(&self) -> Option<Expr> {
+        self.header_exprs().1
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+
+    /// The expressions before and after the `;` that ends the condition, which is the last `;`
+    /// that is not part of the initializer
+    fn header_exprs(&self) -> (Option<Expr>, Option<Expr>) {
+        let mut condition = None;
+        let mut step = None;
+        let last_semicolon = significant_tokens(&self.syntax)
+            .filter(|token| token.kind() == SyntaxKind::SymSemiColon)
+            .last();
+
+        let mut after = false;
+        for element in self.syntax.children_with_tokens() {
+            match element {
+                rowan::NodeOrToken::Token(token) => {
+                    if Some(&token) == last_semicolon.as_ref() {
+                        after = true;
+                    }
+                }
+                rowan::NodeOrToken::Node(node) => {
+                    if let Some(expr) = Expr::cast(node) {
+                        if after {
+                            step = Some(expr);
+                        } else {
+                            condition = Some(expr);
+                        }
+                    }
+                }
+            }
+        }
+
+        (condition, step)
+    }
+}
+
+impl SwitchStmt {
+    /// The value being switched on
+    pub fn scrutinee(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+
+    pub fn clauses(&self) -> impl Iterator<Item = SwitchClause> {
+        children(&self.syntax)
+    }
+}
+
+impl CaseClause {
+    pub fn values(&self) -> impl Iterator<Item = Expr> {
+        children(&self.syntax)
+    }
+
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.syntax)
+    }
+}
+
+impl DefaultClause {
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.syntax)
+    }
+}
+
+impl ReturnStmt {
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl ThrowStmt {
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl TryStmt {
+    pub fn body(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+
+    pub fn catch_clauses(&self) -> impl Iterator<Item = CatchClause> {
+        children(&self.syntax)
+    }
+}
+
+impl CatchClause {
+    pub fn param(&self) -> Option<Param> {
+        child(&self.syntax)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+}
+
+impl UnsafeBlock {
+    pub fn block(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+}
+
+impl ExprStmt {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl Literal {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        significant_tokens(&self.syntax).next()
+    }
+}
+
+impl PathExpr {
+    pub fn path(&self) -> Option<Path> {
+        child(&self.syntax)
+    }
+}
+
+impl BinaryExpr {
+    pub fn lhs(&self) -> Option<Expr> {
+        children(&self.syntax).next()
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.syntax).nth(1)
+    }
+
+    pub fn op_token(&self) -> Option<SyntaxToken> {
+        significant_tokens(&self.syntax).next()
+    }
+
+    pub fn op_kind(&self) -> Option<TokenKind> {
+        self.op_token()?.kind().to_token_kind()
+    }
+}
+
+impl PrefixExpr {
+    pub fn op_token(&self) -> Option<SyntaxToken> {
+        significant_tokens(&self.syntax).next()
+    }
+
+    pub fn op_kind(&self) -> Option<TokenKind> {
+        self.op_token()?.kind().to_token_kind()
+    }
+
+    pub fn operand(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl TernaryExpr {
+    pub fn condition(&self) -> Option<Expr> {
+        children(&self.syntax).next()
+    }
+
+    pub fn then_expr(&self) -> Option<Expr> {
+        children(&self.syntax).nth(1)
+    }
+
+    pub fn else_expr(&self) -> Option<Expr> {
+        children(&self.syntax).nth(2)
+    }
+}
+
+impl CallExpr {
+    pub fn callee(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.syntax)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Expr> {
+        self.arg_list().into_iter().flat_map(|list| list.args())
+    }
+}
+
+impl ArgList {
+    pub fn args(&self) -> impl Iterator<Item = Expr> {
+        children(&self.syntax)
+    }
+}
+
+impl IndexExpr {
+    pub fn base(&self) -> Option<Expr> {
+        children(&self.syntax).next()
+    }
+
+    pub fn index(&self) -> Option<Expr> {
+        children(&self.syntax).nth(1)
+    }
+}
+
+impl MemberExpr {
+    pub fn receiver(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl CastExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl NewExpr {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.syntax)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Expr> {
+        self.arg_list().into_iter().flat_map(|list| list.args())
+    }
+}
+
+impl ParenExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::parse;
+
+    fn source_file(text: &str) -> SourceFile {
+        let parse = parse(text);
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        parse.tree()
+    }
+
+    fn text(node: &impl AstNode) -> String {
+        node.syntax().text().to_string()
+    }
+
+    #[test]
+    fn func_decl() {
+        let file = source_file("public func add(a: i32, b: i32 = 1) i32 { return a + b; }");
+        let Some(Item::FuncDecl(func)) = file.items().next() else {
+            panic!("not a function");
+        };
+
+        assert_eq!(func.name().unwrap().text(), "add");
+        assert!(func.has_modifier(TokenKind::KwordPublic));
+        assert!(!func.has_modifier(TokenKind::KwordStatic));
+        assert!(!func.is_operator());
+
+        let params = func.params().collect::<Vec<_>>();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[1].name().unwrap().text(), "b");
+        assert_eq!(text(&params[1].ty().unwrap()), "i32");
+        assert_eq!(text(&params[1].default_value().unwrap()), "1");
+        assert_eq!(text(&func.ret_type().unwrap().ty().unwrap()), "i32");
+
+        let body = func.body().unwrap();
+        let Some(Stmt::ReturnStmt(ret)) = body.statements().next() else {
+            panic!("not a return");
+        };
+        let Some(Expr::BinaryExpr(sum)) = ret.value() else {
+            panic!("not a binary expression");
+        };
+        assert_eq!(sum.op_kind(), Some(TokenKind::OpPlus));
+        assert_eq!(sum.op_token().unwrap().text(), "+");
+        assert_eq!(text(&sum.lhs().unwrap()), "a");
+        assert_eq!(text(&sum.rhs().unwrap()), "b");
+    }
+
+    #[test]
+    fn operator_name() {
+        let file = source_file("func operator == (a: A, b: A) bool;");
+        let Some(Item::FuncDecl(func)) = file.items().next() else {
+            panic!("not a function");
+        };
+
+        assert!(func.is_operator());
+        assert_eq!(func.name().unwrap().text(), "operator==");
+        assert!(func.body().is_none());
+    }
+
+    #[test]
+    fn class_decl() {
+        let file =
+            source_file("class Dog extends Animal implements Pet { name: String; func bark() {} }");
+        let Some(Item::ClassDecl(class)) = file.items().next() else {
+            panic!("not a class");
+        };
+
+        assert_eq!(class.name().unwrap().text(), "Dog");
+        let bases = class.extends_clause().unwrap().types().collect::<Vec<_>>();
+        assert_eq!(bases.len(), 1);
+        assert_eq!(text(&bases[0]), "Animal");
+        assert_eq!(class.implements_clause().unwrap().types().count(), 1);
+
+        let members = class.members().collect::<Vec<_>>();
+        assert!(
+            matches!(&members[0], Item::FieldDecl(field) if field.name().unwrap().text() == "name")
+        );
+        assert!(matches!(&members[1], Item::FuncDecl(_)));
+    }
+
+    #[test]
+    fn if_stmt() {
+        let file = source_file("func f() { if a { } else if b { } else { x; } }");
+        let Some(Item::FuncDecl(func)) = file.items().next() else {
+            panic!("not a function");
+        };
+        let Some(Stmt::IfStmt(if_stmt)) = func.body().unwrap().statements().next() else {
+            panic!("not an if");
+        };
+
+        assert_eq!(text(&if_stmt.condition().unwrap()), "a");
+        assert_eq!(text(&if_stmt.then_branch().unwrap()), "{ }");
+
+        let Some(ElseBranch::If(else_if)) = if_stmt.else_branch() else {
+            panic!("not an else if");
+        };
+        assert_eq!(text(&else_if.condition().unwrap()), "b");
+        let Some(ElseBranch::Block(block)) = else_if.else_branch() else {
+            panic!("not an else");
+        };
+        assert_eq!(block.statements().count(), 1);
+    }
+
+    #[test]
+    fn for_stmt_parts() {
+        let file = source_file("func f() { for (let i = 0; i < n; i += 1) { } for (;;) { } }");
+        let Some(Item::FuncDecl(func)) = file.items().next() else {
+            panic!("not a function");
+        };
+        let loops = func
+            .body()
+            .unwrap()
+            .statements()
+            .filter_map(|stmt| match stmt {
+                Stmt::ForStmt(for_stmt) => Some(for_stmt),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert!(matches!(loops[0].initializer(), Some(Stmt::VarDecl(_))));
+        assert_eq!(text(&loops[0].condition().unwrap()), "i < n");
+        assert_eq!(text(&loops[0].step().unwrap()), "i += 1");
+
+        assert!(loops[1].initializer().is_none());
+        assert!(loops[1].condition().is_none());
+        assert!(loops[1].step().is_none());
+        assert!(loops[1].body().is_some());
+    }
+
+    #[test]
+    fn raw_identifier_names() {
+        let file = source_file("func r#class(r#type: std::r#new) {}");
+        let Some(Item::FuncDecl(func)) = file.items().next() else {
+            panic!("not a function");
+        };
+
+        assert_eq!(func.name().unwrap().text(), "class");
+        let param = func.params().next().unwrap();
+        assert_eq!(param.name().unwrap().text(), "type");
+        let Some(Type::PathType(ty)) = param.ty() else {
+            panic!("not a path type");
+        };
+        assert_eq!(ty.path().unwrap().text(), "std::new");
+    }
+
+    #[test]
+    fn missing_parts_are_none() {
+        let parse = parse("func (a: ) {");
+        let Some(Item::FuncDecl(func)) = parse.tree().items().next() else {
+            panic!("not a function");
+        };
+
+        assert!(func.name().is_none());
+        assert!(func.params().next().unwrap().ty().is_none());
+        assert!(func.ret_type().is_none());
+        assert_eq!(func.body().unwrap().statements().count(), 0);
+    }
+}
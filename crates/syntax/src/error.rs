@@ -0,0 +1,123 @@
+use std::fmt;
+
+use crate::ast::Span;
+use crate::lexer::LineEnding;
+
+/// The different kinds of errors the lexer can run into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A `"` string that is not closed before the end of the line
+    UnterminatedString,
+    /// A `'` char that is not closed right after its character
+    UnterminatedChar,
+    /// A `''` char without any character in it
+    EmptyChar,
+    /// A `/*` comment that is not closed before the end of the file
+    UnterminatedBlockComment,
+    /// A `\` escape sequence that is not one we know about
+    InvalidEscape,
+    /// A byte that can't start any token
+    StrayByte(u8),
+    /// A non-ASCII character that is not allowed outside of strings and comments
+    UnexpectedChar(char),
+    /// A number literal without any digits where some are needed, like `0x` or `1e`
+    MalformedNumber,
+    /// A digit that is too big for the radix of its number, like the `2` in `0b102`
+    InvalidDigit(u32),
+    /// A number suffix that is not a known type, or doesn't fit the number, like `12abc` or `1.5u8`
+    InvalidNumberSuffix,
+    /// A byte that is not valid at this position in a UTF-8 sequence
+    InvalidUtf8,
+    /// A line ending that differs from the first one in the file, only reported when
+    /// [`crate::lexer::LexerOptions::report_mixed_line_endings`] is set
+    MixedLineEndings {
+        expected: LineEnding,
+        found: LineEnding,
+    },
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexErrorKind::UnterminatedString => write!(f, "unterminated string literal"),
+            LexErrorKind::UnterminatedChar => write!(f, "unterminated character literal"),
+            LexErrorKind::EmptyChar => write!(f, "empty character literal"),
+            LexErrorKind::UnterminatedBlockComment => write!(f, "unterminated block comment"),
+            LexErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            LexErrorKind::StrayByte(byte) if byte.is_ascii_graphic() => {
+                write!(f, "unexpected character `{}`", *byte as char)
+            }
+            LexErrorKind::StrayByte(byte) => write!(f, "unexpected byte 0x{:02X}", byte),
+            LexErrorKind::UnexpectedChar(c) => {
+                write!(f, "unexpected character `{}` (U+{:04X})", c, *c as u32)
+            }
+            LexErrorKind::MalformedNumber => write!(f, "malformed number literal"),
+            LexErrorKind::InvalidDigit(radix) => {
+                write!(f, "invalid digit for a base {} number", radix)
+            }
+            LexErrorKind::InvalidNumberSuffix => write!(f, "invalid suffix for a number literal"),
+            LexErrorKind::InvalidUtf8 => write!(f, "invalid UTF-8 sequence"),
+            LexErrorKind::MixedLineEndings { expected, found } => write!(
+                f,
+                "mixed line endings, found {} but the file uses {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+/// An error produced while lexing, pointing at the offending part of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+    pub message: String,
+}
+
+impl LexError {
+    pub fn new(kind: LexErrorKind, span: Span) -> Self {
+        LexError {
+            kind,
+            span,
+            message: kind.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+/// An error produced while parsing
+///
+/// The parser carries on after an error, so the tree is still built around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        ParseError {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
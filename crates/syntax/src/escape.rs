@@ -0,0 +1,152 @@
+//! Escape sequences inside of string and char literals
+//!
+//! Both the lexer (to report bad escapes) and [`unescape`] (to get the cooked value of a literal)
+//! go through [`scan_escape`], so they can never disagree about what is a valid escape.
+//!
+//! Supported escapes:
+//! * `\n`, `\r`, `\t`, `\\`, `\"`, `\'` and `\0`
+//! * `\xNN`, two hex digits of an ASCII value (`\x00` to `\x7F`)
+//! * `\u{NNNN}`, one to six hex digits of a unicode scalar value
+
+/// Scans a single escape sequence.
+///
+/// `text` starts right *after* the backslash. Returns the escaped character (or [`None`] if the
+/// escape is invalid), and how many bytes of `text` belong to the escape.
+///
+/// A line break (or the end of `text`) is never consumed, so an unterminated literal stays
+/// unterminated.
+pub(crate) fn scan_escape(text: &str) -> (Option<char>, usize) {
+    let mut chars = text.chars();
+
+    let first = match chars.next() {
+        Some('\n' | '\r') | None => return (None, 0),
+        Some(c) => c,
+    };
+
+    match first {
+        'n' => (Some('\n'), 1),
+        'r' => (Some('\r'), 1),
+        't' => (Some('\t'), 1),
+        '\\' => (Some('\\'), 1),
+        '"' => (Some('"'), 1),
+        '\'' => (Some('\''), 1),
+        '0' => (Some('\0'), 1),
+        'x' => {
+            let digits = hex_digits(&text[1..], 2);
+
+            if digits.len() != 2 {
+                return (None, 1 + digits.len());
+            }
+
+            match u8::from_str_radix(digits, 16) {
+                Ok(value) if value <= 0x7F => (Some(value as char), 3),
+                _ => (None, 3),
+            }
+        }
+        'u' => {
+            if !text[1..].starts_with('{') {
+                return (None, 1);
+            }
+
+            // Read every hex digit, so an overlong escape is reported as a whole
+            let digits = hex_digits(&text[2..], usize::MAX);
+            let mut len = 2 + digits.len();
+
+            if !text[len..].starts_with('}') {
+                return (None, len);
+            }
+            len += 1;
+
+            if digits.is_empty() || digits.len() > 6 {
+                return (None, len);
+            }
+
+            let value = u32::from_str_radix(digits, 16)
+                .ok()
+                .and_then(char::from_u32);
+            (value, len)
+        }
+        other => (None, other.len_utf8()),
+    }
+}
+
+/// Leading hex digits of `text`, at most `max` of them
+fn hex_digits(text: &str, max: usize) -> &str {
+    let len = text
+        .bytes()
+        .take(max)
+        .take_while(|byte| byte.is_ascii_hexdigit())
+        .count();
+
+    &text[..len]
+}
+
+/// Cooks the contents of a literal (without its quotes), replacing escapes with the characters
+/// they stand for.
+///
+/// Invalid escapes are kept as they are written, the lexer has already reported them.
+pub fn unescape(text: &str) -> String {
+    let mut cooked = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(index) = rest.find('\\') {
+        cooked.push_str(&rest[..index]);
+
+        let escape = &rest[index + 1..];
+        let (value, len) = scan_escape(escape);
+
+        match value {
+            Some(c) => cooked.push(c),
+            None => cooked.push_str(&rest[index..index + 1 + len]),
+        }
+
+        rest = &escape[len..];
+    }
+
+    cooked.push_str(rest);
+    cooked
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn simple_escapes() {
+        assert_eq!(scan_escape("n"), (Some('\n'), 1));
+        assert_eq!(scan_escape("\\rest"), (Some('\\'), 1));
+        assert_eq!(scan_escape("0"), (Some('\0'), 1));
+        assert_eq!(scan_escape("q"), (None, 1));
+        assert_eq!(scan_escape("é"), (None, 2));
+        assert_eq!(scan_escape("\n"), (None, 0));
+        assert_eq!(scan_escape(""), (None, 0));
+    }
+
+    #[test]
+    fn hex_escapes() {
+        assert_eq!(scan_escape("x41"), (Some('A'), 3));
+        assert_eq!(scan_escape("x7F"), (Some('\x7F'), 3));
+        assert_eq!(scan_escape("xFF"), (None, 3));
+        assert_eq!(scan_escape("x4"), (None, 2));
+        assert_eq!(scan_escape("xg"), (None, 1));
+    }
+
+    #[test]
+    fn unicode_escapes() {
+        assert_eq!(scan_escape("u{1F600}"), (Some('😀'), 8));
+        assert_eq!(scan_escape("u{41}rest"), (Some('A'), 5));
+        assert_eq!(scan_escape("u{}"), (None, 3));
+        assert_eq!(scan_escape("u{D800}"), (None, 7));
+        assert_eq!(scan_escape("u{1234567}"), (None, 10));
+        assert_eq!(scan_escape("u{41"), (None, 4));
+        assert_eq!(scan_escape("u41"), (None, 1));
+    }
+
+    #[test]
+    fn unescape_text() {
+        assert_eq!(unescape(r"a\tb\\c\u{e9}"), "a\tb\\cé");
+        assert_eq!(unescape(r#"say \"hi\""#), "say \"hi\"");
+        assert_eq!(unescape(r"bad \q escape"), r"bad \q escape");
+        assert_eq!(unescape(r"trailing \"), r"trailing \");
+    }
+}
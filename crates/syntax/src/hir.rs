@@ -0,0 +1,545 @@
+//! The lowered syntax tree that semantic passes work on
+//!
+//! [`lower`] turns a [`crate::ast::SourceFile`] into a [`Program`] that lives in an
+//! [`oxc_allocator::Allocator`]. Unlike the concrete tree it has no trivia, punctuation or
+//! parentheses, so it is much smaller and faster to walk. Nothing in it needs dropping, the
+//! whole tree goes away with its allocator, so keep one allocator per revision of the
+//! workspace and reset it when the sources change.
+//!
+//! Parts that the parser reported as missing become [`Expr::Error`], [`Type::Error`] or
+//! empty blocks, so passes never have to care about half typed code.
+
+use std::ops::BitOr;
+
+use oxc_allocator::{Box, Vec};
+
+use crate::ast::Span;
+use crate::number::NumberLiteral;
+
+mod lower;
+
+pub use lower::lower;
+
+#[derive(Debug)]
+pub struct Program<'a> {
+    pub span: Span,
+    pub items: Vec<'a, Item<'a>>,
+}
+
+/// A name together with where it is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ident<'a> {
+    pub name: &'a str,
+    pub span: Span,
+}
+
+/// A path like `std::io::print` or `Vec<i32>::new`
+#[derive(Debug)]
+pub struct Path<'a> {
+    pub span: Span,
+    pub segments: Vec<'a, PathSegment<'a>>,
+}
+
+#[derive(Debug)]
+pub struct PathSegment<'a> {
+    pub name: Ident<'a>,
+    pub generic_args: Vec<'a, Type<'a>>,
+}
+
+/// The modifiers of a declaration, like `public` or `static`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifiers(u16);
+
+impl Modifiers {
+    pub const PUBLIC: Modifiers = Modifiers(1 << 0);
+    pub const PRIVATE: Modifiers = Modifiers(1 << 1);
+    pub const STATIC: Modifiers = Modifiers(1 << 2);
+    pub const VIRTUAL: Modifiers = Modifiers(1 << 3);
+    pub const OVERRIDE: Modifiers = Modifiers(1 << 4);
+    pub const ABSTRACT: Modifiers = Modifiers(1 << 5);
+    pub const FINAL: Modifiers = Modifiers(1 << 6);
+    pub const INLINE: Modifiers = Modifiers(1 << 7);
+    pub const CONSTEXPR: Modifiers = Modifiers(1 << 8);
+
+    pub fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Modifiers) {
+        self.0 |= other.0;
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 | other.0)
+    }
+}
+
+#[derive(Debug)]
+pub enum Item<'a> {
+    Func(Box<'a, Func<'a>>),
+    Class(Box<'a, Class<'a>>),
+    Struct(Box<'a, Struct<'a>>),
+    Enum(Box<'a, Enum<'a>>),
+    Interface(Box<'a, Interface<'a>>),
+    Namespace(Box<'a, Namespace<'a>>),
+    TypeAlias(Box<'a, TypeAlias<'a>>),
+    Import(Box<'a, Import<'a>>),
+    Extern(Box<'a, Extern<'a>>),
+    Macro(Box<'a, Macro<'a>>),
+    Var(Box<'a, VarDecl<'a>>),
+    Field(Box<'a, Field<'a>>),
+}
+
+#[derive(Debug)]
+pub struct Func<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    /// `operator==` for operator overloads
+    pub name: Option<Ident<'a>>,
+    pub generic_params: Vec<'a, Ident<'a>>,
+    pub is_operator: bool,
+    pub params: Vec<'a, Param<'a>>,
+    pub ret_type: Option<Type<'a>>,
+    /// [`None`] for declarations ending in `;`
+    pub body: Option<Block<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Param<'a> {
+    pub span: Span,
+    pub name: Option<Ident<'a>>,
+    /// [`None`] for macro parameters, which have no types
+    pub ty: Option<Type<'a>>,
+    pub default_value: Option<Expr<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Class<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub name: Option<Ident<'a>>,
+    pub generic_params: Vec<'a, Ident<'a>>,
+    pub extends: Vec<'a, Type<'a>>,
+    pub implements: Vec<'a, Type<'a>>,
+    pub members: Vec<'a, Item<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Struct<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub name: Option<Ident<'a>>,
+    pub generic_params: Vec<'a, Ident<'a>>,
+    pub members: Vec<'a, Item<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Interface<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub name: Option<Ident<'a>>,
+    pub generic_params: Vec<'a, Ident<'a>>,
+    pub extends: Vec<'a, Type<'a>>,
+    pub members: Vec<'a, Item<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Enum<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub name: Option<Ident<'a>>,
+    pub generic_params: Vec<'a, Ident<'a>>,
+    pub variants: Vec<'a, Variant<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Variant<'a> {
+    pub span: Span,
+    pub name: Option<Ident<'a>>,
+    pub fields: Vec<'a, Type<'a>>,
+    pub value: Option<Expr<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Namespace<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub path: Option<Path<'a>>,
+    pub items: Vec<'a, Item<'a>>,
+}
+
+#[derive(Debug)]
+pub struct TypeAlias<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub name: Option<Ident<'a>>,
+    pub generic_params: Vec<'a, Ident<'a>>,
+    pub ty: Type<'a>,
+}
+
+#[derive(Debug)]
+pub struct Import<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub path: Option<Path<'a>>,
+    pub alias: Option<Ident<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Extern<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    /// The cooked ABI string, like `C`
+    pub abi: Option<&'a str>,
+    pub items: Vec<'a, Item<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Macro<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub name: Option<Ident<'a>>,
+    pub params: Vec<'a, Param<'a>>,
+    /// Where the body is, with its braces, macros are expanded from the source text
+    pub body: Option<Span>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarKind {
+    Let,
+    Const,
+}
+
+#[derive(Debug)]
+pub struct VarDecl<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub kind: VarKind,
+    pub is_mut: bool,
+    pub name: Option<Ident<'a>>,
+    pub ty: Option<Type<'a>>,
+    pub initializer: Option<Expr<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Field<'a> {
+    pub span: Span,
+    pub modifiers: Modifiers,
+    pub name: Option<Ident<'a>>,
+    pub ty: Type<'a>,
+}
+
+#[derive(Debug)]
+pub enum Type<'a> {
+    Path(Box<'a, Path<'a>>),
+    Pointer(Box<'a, PointerType<'a>>),
+    Ref(Box<'a, RefType<'a>>),
+    Array(Box<'a, ArrayType<'a>>),
+    /// A missing or malformed type, the parser has already reported it
+    Error(Span),
+}
+
+/// What a pointer allows through it, `*T`, `*const T` or `*mut T`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    Plain,
+    Const,
+    Mut,
+}
+
+#[derive(Debug)]
+pub struct PointerType<'a> {
+    pub span: Span,
+    pub kind: PointerKind,
+    pub pointee: Type<'a>,
+}
+
+#[derive(Debug)]
+pub struct RefType<'a> {
+    pub span: Span,
+    pub is_mut: bool,
+    pub referent: Type<'a>,
+}
+
+#[derive(Debug)]
+pub struct ArrayType<'a> {
+    pub span: Span,
+    pub element: Type<'a>,
+}
+
+#[derive(Debug)]
+pub struct Block<'a> {
+    pub span: Span,
+    pub stmts: Vec<'a, Stmt<'a>>,
+}
+
+#[derive(Debug)]
+pub enum Stmt<'a> {
+    Var(Box<'a, VarDecl<'a>>),
+    Expr(Expr<'a>),
+    Block(Box<'a, Block<'a>>),
+    If(Box<'a, IfStmt<'a>>),
+    While(Box<'a, WhileStmt<'a>>),
+    DoWhile(Box<'a, WhileStmt<'a>>),
+    For(Box<'a, ForStmt<'a>>),
+    Switch(Box<'a, SwitchStmt<'a>>),
+    Break(Span),
+    Continue(Span),
+    Return(Box<'a, ReturnStmt<'a>>),
+    Throw(Box<'a, ThrowStmt<'a>>),
+    Try(Box<'a, TryStmt<'a>>),
+    Unsafe(Box<'a, Block<'a>>),
+    /// A declaration nested in a block
+    Item(Item<'a>),
+}
+
+#[derive(Debug)]
+pub struct IfStmt<'a> {
+    pub span: Span,
+    pub condition: Expr<'a>,
+    pub then_branch: Block<'a>,
+    pub else_branch: Option<ElseBranch<'a>>,
+}
+
+#[derive(Debug)]
+pub enum ElseBranch<'a> {
+    If(Box<'a, IfStmt<'a>>),
+    Block(Box<'a, Block<'a>>),
+}
+
+/// A `while` loop, or a `do ... while` loop
+#[derive(Debug)]
+pub struct WhileStmt<'a> {
+    pub span: Span,
+    pub condition: Expr<'a>,
+    pub body: Block<'a>,
+}
+
+#[derive(Debug)]
+pub struct ForStmt<'a> {
+    pub span: Span,
+    pub initializer: Option<Stmt<'a>>,
+    pub condition: Option<Expr<'a>>,
+    pub step: Option<Expr<'a>>,
+    pub body: Block<'a>,
+}
+
+#[derive(Debug)]
+pub struct SwitchStmt<'a> {
+    pub span: Span,
+    pub scrutinee: Expr<'a>,
+    pub clauses: Vec<'a, SwitchClause<'a>>,
+}
+
+/// A `case` clause, or the `default` one which has no values
+#[derive(Debug)]
+pub struct SwitchClause<'a> {
+    pub span: Span,
+    pub is_default: bool,
+    pub values: Vec<'a, Expr<'a>>,
+    pub stmts: Vec<'a, Stmt<'a>>,
+}
+
+#[derive(Debug)]
+pub struct ReturnStmt<'a> {
+    pub span: Span,
+    pub value: Option<Expr<'a>>,
+}
+
+#[derive(Debug)]
+pub struct ThrowStmt<'a> {
+    pub span: Span,
+    pub value: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct TryStmt<'a> {
+    pub span: Span,
+    pub body: Block<'a>,
+    pub catch_clauses: Vec<'a, CatchClause<'a>>,
+}
+
+#[derive(Debug)]
+pub struct CatchClause<'a> {
+    pub span: Span,
+    pub param: Option<Param<'a>>,
+    pub body: Block<'a>,
+}
+
+/// An expression, parentheses are dropped while lowering
+#[derive(Debug)]
+pub enum Expr<'a> {
+    Literal(Box<'a, Literal<'a>>),
+    Path(Box<'a, Path<'a>>),
+    Binary(Box<'a, BinaryExpr<'a>>),
+    Prefix(Box<'a, PrefixExpr<'a>>),
+    Ternary(Box<'a, TernaryExpr<'a>>),
+    Call(Box<'a, CallExpr<'a>>),
+    Index(Box<'a, IndexExpr<'a>>),
+    Member(Box<'a, MemberExpr<'a>>),
+    Cast(Box<'a, CastExpr<'a>>),
+    New(Box<'a, NewExpr<'a>>),
+    /// A missing or malformed expression, the parser has already reported it
+    Error(Span),
+}
+
+impl Expr<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Literal(expr) => expr.span,
+            Expr::Path(expr) => expr.span,
+            Expr::Binary(expr) => expr.span,
+            Expr::Prefix(expr) => expr.span,
+            Expr::Ternary(expr) => expr.span,
+            Expr::Call(expr) => expr.span,
+            Expr::Index(expr) => expr.span,
+            Expr::Member(expr) => expr.span,
+            Expr::Cast(expr) => expr.span,
+            Expr::New(expr) => expr.span,
+            Expr::Error(span) => *span,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Literal<'a> {
+    pub span: Span,
+    pub value: LiteralValue<'a>,
+}
+
+/// The value of a literal, strings and chars are cooked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LiteralValue<'a> {
+    Number(NumberLiteral),
+    String(&'a str),
+    Char(char),
+    Bool(bool),
+    Null,
+    /// A malformed literal, the lexer has already reported it
+    Invalid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    Assign,
+    AddAssign,
+    SubAssign,
+    MulAssign,
+    DivAssign,
+    ModAssign,
+    BitAndAssign,
+    BitOrAssign,
+    BitXorAssign,
+    ShlAssign,
+    ShrAssign,
+}
+
+impl BinaryOp {
+    pub fn is_assignment(self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Assign
+                | BinaryOp::AddAssign
+                | BinaryOp::SubAssign
+                | BinaryOp::MulAssign
+                | BinaryOp::DivAssign
+                | BinaryOp::ModAssign
+                | BinaryOp::BitAndAssign
+                | BinaryOp::BitOrAssign
+                | BinaryOp::BitXorAssign
+                | BinaryOp::ShlAssign
+                | BinaryOp::ShrAssign
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixOp {
+    Not,
+    BitNot,
+    Neg,
+}
+
+#[derive(Debug)]
+pub struct BinaryExpr<'a> {
+    pub span: Span,
+    pub op: BinaryOp,
+    pub lhs: Expr<'a>,
+    pub rhs: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct PrefixExpr<'a> {
+    pub span: Span,
+    pub op: PrefixOp,
+    pub operand: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct TernaryExpr<'a> {
+    pub span: Span,
+    pub condition: Expr<'a>,
+    pub then_expr: Expr<'a>,
+    pub else_expr: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct CallExpr<'a> {
+    pub span: Span,
+    pub callee: Expr<'a>,
+    pub args: Vec<'a, Expr<'a>>,
+}
+
+#[derive(Debug)]
+pub struct IndexExpr<'a> {
+    pub span: Span,
+    pub base: Expr<'a>,
+    pub index: Expr<'a>,
+}
+
+#[derive(Debug)]
+pub struct MemberExpr<'a> {
+    pub span: Span,
+    pub receiver: Expr<'a>,
+    pub member: Option<Ident<'a>>,
+}
+
+#[derive(Debug)]
+pub struct CastExpr<'a> {
+    pub span: Span,
+    pub expr: Expr<'a>,
+    pub ty: Type<'a>,
+}
+
+#[derive(Debug)]
+pub struct NewExpr<'a> {
+    pub span: Span,
+    pub ty: Type<'a>,
+    pub args: Vec<'a, Expr<'a>>,
+}
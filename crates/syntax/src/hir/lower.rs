@@ -0,0 +1,778 @@
+//! Lowering from the typed concrete tree in [`crate::ast`] to [`super::Program`]
+
+use oxc_allocator::{Allocator, Box, Vec};
+use rowan::TextRange;
+
+use super::*;
+use crate::ast::{self, AstNode, HasGenericParams, HasModifiers, HasName, Token, TokenKind};
+use crate::syntax_node::{SyntaxNode, SyntaxToken};
+
+/// Lowers a parsed file into `allocator`
+///
+/// Every name and string in the result is copied into the allocator, so the result does not
+/// borrow from `file` or from the source text.
+pub fn lower<'a>(allocator: &'a Allocator, file: &ast::SourceFile) -> Program<'a> {
+    let lower = Lower { allocator };
+    Program {
+        span: span(file.syntax()),
+        items: lower.items(file.items()),
+    }
+}
+
+fn to_span(range: TextRange) -> Span {
+    Span {
+        start: range.start().into(),
+        end: range.end().into(),
+    }
+}
+
+fn span(node: &SyntaxNode) -> Span {
+    to_span(node.text_range())
+}
+
+/// An empty span at the end of `parent`, for the parts of it that are missing
+fn missing(parent: &SyntaxNode) -> Span {
+    let end = parent.text_range().end().into();
+    Span { start: end, end }
+}
+
+/// The cooked value of a string or char token
+fn cooked(token: &SyntaxToken) -> Option<String> {
+    let text = token.text();
+    let token = Token {
+        kind: token.kind().to_token_kind()?,
+        span: Span {
+            start: 0,
+            end: text.len(),
+        },
+    };
+    token.unescape(text)
+}
+
+fn modifiers(node: &impl HasModifiers) -> Modifiers {
+    let mut modifiers = Modifiers::default();
+    let tokens = node
+        .modifier_list()
+        .into_iter()
+        .flat_map(|list| list.modifiers());
+
+    for token in tokens {
+        let modifier = match token.kind().to_token_kind() {
+            Some(TokenKind::KwordPublic) => Modifiers::PUBLIC,
+            Some(TokenKind::KwordPrivate) => Modifiers::PRIVATE,
+            Some(TokenKind::KwordStatic) => Modifiers::STATIC,
+            Some(TokenKind::KwordVirtual) => Modifiers::VIRTUAL,
+            Some(TokenKind::KwordOverride) => Modifiers::OVERRIDE,
+            Some(TokenKind::KwordAbstract) => Modifiers::ABSTRACT,
+            Some(TokenKind::KwordFinal) => Modifiers::FINAL,
+            Some(TokenKind::KwordInline) => Modifiers::INLINE,
+            Some(TokenKind::KwordConstexpr) => Modifiers::CONSTEXPR,
+            _ => continue,
+        };
+        modifiers.insert(modifier);
+    }
+
+    modifiers
+}
+
+fn binary_op(kind: TokenKind) -> Option<BinaryOp> {
+    let op = match kind {
+        TokenKind::OpPlus => BinaryOp::Add,
+        TokenKind::OpMinus => BinaryOp::Sub,
+        TokenKind::OpMul => BinaryOp::Mul,
+        TokenKind::OpDiv => BinaryOp::Div,
+        TokenKind::OpMod => BinaryOp::Mod,
+        TokenKind::OpEqeq => BinaryOp::Eq,
+        TokenKind::OpNoteq => BinaryOp::NotEq,
+        TokenKind::OpLt => BinaryOp::Lt,
+        TokenKind::OpLteq => BinaryOp::LtEq,
+        TokenKind::OpGt => BinaryOp::Gt,
+        TokenKind::OpGteq => BinaryOp::GtEq,
+        TokenKind::OpAnd => BinaryOp::And,
+        TokenKind::OpOr => BinaryOp::Or,
+        TokenKind::OpBitAnd => BinaryOp::BitAnd,
+        TokenKind::OpBitOr => BinaryOp::BitOr,
+        TokenKind::OpBitXor => BinaryOp::BitXor,
+        TokenKind::OpBitLshift => BinaryOp::Shl,
+        TokenKind::OpBitRshift => BinaryOp::Shr,
+        TokenKind::OpEq => BinaryOp::Assign,
+        TokenKind::OpPluseq => BinaryOp::AddAssign,
+        TokenKind::OpMinuseq => BinaryOp::SubAssign,
+        TokenKind::OpMuleq => BinaryOp::MulAssign,
+        TokenKind::OpDiveq => BinaryOp::DivAssign,
+        TokenKind::OpModEq => BinaryOp::ModAssign,
+        TokenKind::OpBitAndEq => BinaryOp::BitAndAssign,
+        TokenKind::OpBitOrEq => BinaryOp::BitOrAssign,
+        TokenKind::OpBitXorEq => BinaryOp::BitXorAssign,
+        TokenKind::OpBitLshiftEq => BinaryOp::ShlAssign,
+        TokenKind::OpBitRshiftEq => BinaryOp::ShrAssign,
+        _ => return None,
+    };
+    Some(op)
+}
+
+fn prefix_op(kind: TokenKind) -> Option<PrefixOp> {
+    let op = match kind {
+        TokenKind::OpNot => PrefixOp::Not,
+        TokenKind::OpBitNot => PrefixOp::BitNot,
+        TokenKind::OpMinus => PrefixOp::Neg,
+        _ => return None,
+    };
+    Some(op)
+}
+
+struct Lower<'a> {
+    allocator: &'a Allocator,
+}
+
+impl<'a> Lower<'a> {
+    fn boxed<T>(&self, value: T) -> Box<'a, T> {
+        Box::new_in(value, self.allocator)
+    }
+
+    fn vec<T>(&self, iter: impl IntoIterator<Item = T>) -> Vec<'a, T> {
+        Vec::from_iter_in(iter, self.allocator)
+    }
+
+    fn str(&self, text: &str) -> &'a str {
+        self.allocator.alloc_str(text)
+    }
+
+    fn name(&self, node: &impl HasName) -> Option<Ident<'a>> {
+        let name = node.name()?;
+        Some(Ident {
+            name: self.str(&name.text()),
+            span: span(name.syntax()),
+        })
+    }
+
+    fn generic_params(&self, node: &impl HasGenericParams) -> Vec<'a, Ident<'a>> {
+        self.vec(node.generic_params().filter_map(|param| self.name(&param)))
+    }
+
+    fn path(&self, path: &ast::Path) -> Path<'a> {
+        let mut segments = Vec::new_in(self.allocator);
+        for element in path.syntax().children_with_tokens() {
+            match element {
+                rowan::NodeOrToken::Token(token) if token.kind().is_path_segment() => {
+                    segments.push(PathSegment {
+                        name: Ident {
+                            name: self.str(ast::identifier_name(token.text())),
+                            span: to_span(token.text_range()),
+                        },
+                        generic_args: Vec::new_in(self.allocator),
+                    });
+                }
+                rowan::NodeOrToken::Node(node) => {
+                    let (Some(args), Some(segment)) =
+                        (ast::GenericArgList::cast(node), segments.last_mut())
+                    else {
+                        continue;
+                    };
+                    segment.generic_args = self.vec(args.args().map(|ty| self.ty(ty)));
+                }
+                _ => {}
+            }
+        }
+
+        Path {
+            span: span(path.syntax()),
+            segments,
+        }
+    }
+
+    fn items(&self, items: impl Iterator<Item = ast::Item>) -> Vec<'a, Item<'a>> {
+        self.vec(items.map(|item| self.item(item)))
+    }
+
+    fn item(&self, item: ast::Item) -> Item<'a> {
+        match item {
+            ast::Item::FuncDecl(func) => Item::Func(self.boxed(self.func(&func))),
+            ast::Item::ClassDecl(class) => Item::Class(self.boxed(Class {
+                span: span(class.syntax()),
+                modifiers: modifiers(&class),
+                name: self.name(&class),
+                generic_params: self.generic_params(&class),
+                extends: self.types(class.extends_clause().map(|clause| clause.types())),
+                implements: self.types(class.implements_clause().map(|clause| clause.types())),
+                members: self.items(class.members()),
+            })),
+            ast::Item::StructDecl(strukt) => Item::Struct(self.boxed(Struct {
+                span: span(strukt.syntax()),
+                modifiers: modifiers(&strukt),
+                name: self.name(&strukt),
+                generic_params: self.generic_params(&strukt),
+                members: self.items(strukt.members()),
+            })),
+            ast::Item::EnumDecl(enm) => {
+                let variants = enm.variants().map(|variant| Variant {
+                    span: span(variant.syntax()),
+                    name: self.name(&variant),
+                    fields: self.vec(variant.fields().map(|ty| self.ty(ty))),
+                    value: variant.value().map(|value| self.expr(value)),
+                });
+                Item::Enum(self.boxed(Enum {
+                    span: span(enm.syntax()),
+                    modifiers: modifiers(&enm),
+                    name: self.name(&enm),
+                    generic_params: self.generic_params(&enm),
+                    variants: self.vec(variants),
+                }))
+            }
+            ast::Item::InterfaceDecl(interface) => Item::Interface(self.boxed(Interface {
+                span: span(interface.syntax()),
+                modifiers: modifiers(&interface),
+                name: self.name(&interface),
+                generic_params: self.generic_params(&interface),
+                extends: self.types(interface.extends_clause().map(|clause| clause.types())),
+                members: self.items(interface.members()),
+            })),
+            ast::Item::NamespaceDecl(namespace) => Item::Namespace(self.boxed(Namespace {
+                span: span(namespace.syntax()),
+                modifiers: modifiers(&namespace),
+                path: namespace.path().map(|path| self.path(&path)),
+                items: self.items(namespace.items()),
+            })),
+            ast::Item::TypeAlias(alias) => Item::TypeAlias(self.boxed(TypeAlias {
+                span: span(alias.syntax()),
+                modifiers: modifiers(&alias),
+                name: self.name(&alias),
+                generic_params: self.generic_params(&alias),
+                ty: self.ty_or_error(alias.ty(), alias.syntax()),
+            })),
+            ast::Item::ImportDecl(import) => Item::Import(self.boxed(Import {
+                span: span(import.syntax()),
+                modifiers: modifiers(&import),
+                path: import.path().map(|path| self.path(&path)),
+                alias: import.alias().and_then(|alias| self.name(&alias)),
+            })),
+            ast::Item::ExternDecl(ext) => Item::Extern(
+                self.boxed(Extern {
+                    span: span(ext.syntax()),
+                    modifiers: modifiers(&ext),
+                    abi: ext
+                        .abi()
+                        .and_then(|abi| cooked(&abi))
+                        .map(|abi| self.str(&abi)),
+                    items: self.items(ext.items()),
+                }),
+            ),
+            ast::Item::MacroDecl(mac) => {
+                let params = mac.param_list().into_iter().flat_map(|list| list.params());
+                Item::Macro(self.boxed(Macro {
+                    span: span(mac.syntax()),
+                    modifiers: modifiers(&mac),
+                    name: self.name(&mac),
+                    params: self.vec(params.map(|param| self.param(&param))),
+                    body: mac.body().map(|body| span(body.syntax())),
+                }))
+            }
+            ast::Item::VarDecl(var) => Item::Var(self.boxed(self.var_decl(&var))),
+            ast::Item::FieldDecl(field) => Item::Field(self.boxed(Field {
+                span: span(field.syntax()),
+                modifiers: modifiers(&field),
+                name: self.name(&field),
+                ty: self.ty_or_error(field.ty(), field.syntax()),
+            })),
+        }
+    }
+
+    fn func(&self, func: &ast::FuncDecl) -> Func<'a> {
+        Func {
+            span: span(func.syntax()),
+            modifiers: modifiers(func),
+            name: self.name(func),
+            generic_params: self.generic_params(func),
+            is_operator: func.is_operator(),
+            params: self.vec(func.params().map(|param| self.param(&param))),
+            ret_type: func
+                .ret_type()
+                .and_then(|ret| ret.ty())
+                .map(|ty| self.ty(ty)),
+            body: func.body().map(|body| self.block(&body)),
+        }
+    }
+
+    fn param(&self, param: &ast::Param) -> Param<'a> {
+        Param {
+            span: span(param.syntax()),
+            name: self.name(param),
+            ty: param.ty().map(|ty| self.ty(ty)),
+            default_value: param.default_value().map(|value| self.expr(value)),
+        }
+    }
+
+    fn var_decl(&self, var: &ast::VarDecl) -> VarDecl<'a> {
+        VarDecl {
+            span: span(var.syntax()),
+            modifiers: modifiers(var),
+            kind: if var.is_const() {
+                VarKind::Const
+            } else {
+                VarKind::Let
+            },
+            is_mut: var.is_mut(),
+            name: self.name(var),
+            ty: var.ty().map(|ty| self.ty(ty)),
+            initializer: var.initializer().map(|value| self.expr(value)),
+        }
+    }
+
+    fn types(&self, types: Option<impl Iterator<Item = ast::Type>>) -> Vec<'a, Type<'a>> {
+        self.vec(types.into_iter().flatten().map(|ty| self.ty(ty)))
+    }
+
+    fn ty_or_error(&self, ty: Option<ast::Type>, parent: &SyntaxNode) -> Type<'a> {
+        match ty {
+            Some(ty) => self.ty(ty),
+            None => Type::Error(missing(parent)),
+        }
+    }
+
+    fn ty(&self, ty: ast::Type) -> Type<'a> {
+        match ty {
+            ast::Type::PathType(ty) => match ty.path() {
+                Some(path) => Type::Path(self.boxed(self.path(&path))),
+                None => Type::Error(span(ty.syntax())),
+            },
+            ast::Type::PointerType(ty) => {
+                let kind = if ty.is_const() {
+                    PointerKind::Const
+                } else if ty.is_mut() {
+                    PointerKind::Mut
+                } else {
+                    PointerKind::Plain
+                };
+                Type::Pointer(self.boxed(PointerType {
+                    span: span(ty.syntax()),
+                    kind,
+                    pointee: self.ty_or_error(ty.pointee(), ty.syntax()),
+                }))
+            }
+            ast::Type::RefType(ty) => Type::Ref(self.boxed(RefType {
+                span: span(ty.syntax()),
+                is_mut: ty.is_mut(),
+                referent: self.ty_or_error(ty.referent(), ty.syntax()),
+            })),
+            ast::Type::ArrayType(ty) => Type::Array(self.boxed(ArrayType {
+                span: span(ty.syntax()),
+                element: self.ty_or_error(ty.element(), ty.syntax()),
+            })),
+        }
+    }
+
+    fn block(&self, block: &ast::Block) -> Block<'a> {
+        Block {
+            span: span(block.syntax()),
+            stmts: self.stmts(block.statements()),
+        }
+    }
+
+    /// The block of `parent`, or an empty one if the parser didn't find it
+    fn block_or_empty(&self, block: Option<ast::Block>, parent: &SyntaxNode) -> Block<'a> {
+        match block {
+            Some(block) => self.block(&block),
+            None => Block {
+                span: missing(parent),
+                stmts: Vec::new_in(self.allocator),
+            },
+        }
+    }
+
+    fn stmts(&self, stmts: impl Iterator<Item = ast::Stmt>) -> Vec<'a, Stmt<'a>> {
+        self.vec(stmts.map(|stmt| self.stmt(stmt)))
+    }
+
+    fn stmt(&self, stmt: ast::Stmt) -> Stmt<'a> {
+        match stmt {
+            ast::Stmt::VarDecl(var) => Stmt::Var(self.boxed(self.var_decl(&var))),
+            ast::Stmt::ExprStmt(stmt) => Stmt::Expr(self.expr_or_error(stmt.expr(), stmt.syntax())),
+            ast::Stmt::Block(block) => Stmt::Block(self.boxed(self.block(&block))),
+            ast::Stmt::IfStmt(stmt) => Stmt::If(self.boxed(self.if_stmt(&stmt))),
+            ast::Stmt::WhileStmt(stmt) => Stmt::While(self.boxed(WhileStmt {
+                span: span(stmt.syntax()),
+                condition: self.expr_or_error(stmt.condition(), stmt.syntax()),
+                body: self.block_or_empty(stmt.body(), stmt.syntax()),
+            })),
+            ast::Stmt::DoWhileStmt(stmt) => Stmt::DoWhile(self.boxed(WhileStmt {
+                span: span(stmt.syntax()),
+                condition: self.expr_or_error(stmt.condition(), stmt.syntax()),
+                body: self.block_or_empty(stmt.body(), stmt.syntax()),
+            })),
+            ast::Stmt::ForStmt(stmt) => Stmt::For(self.boxed(ForStmt {
+                span: span(stmt.syntax()),
+                initializer: stmt.initializer().map(|init| self.stmt(init)),
+                condition: stmt.condition().map(|cond| self.expr(cond)),
+                step: stmt.step().map(|step| self.expr(step)),
+                body: self.block_or_empty(stmt.body(), stmt.syntax()),
+            })),
+            ast::Stmt::SwitchStmt(stmt) => {
+                let clauses = stmt.clauses().map(|clause| match clause {
+                    ast::SwitchClause::CaseClause(clause) => SwitchClause {
+                        span: span(clause.syntax()),
+                        is_default: false,
+                        values: self.vec(clause.values().map(|value| self.expr(value))),
+                        stmts: self.stmts(clause.statements()),
+                    },
+                    ast::SwitchClause::DefaultClause(clause) => SwitchClause {
+                        span: span(clause.syntax()),
+                        is_default: true,
+                        values: Vec::new_in(self.allocator),
+                        stmts: self.stmts(clause.statements()),
+                    },
+                });
+                Stmt::Switch(self.boxed(SwitchStmt {
+                    span: span(stmt.syntax()),
+                    scrutinee: self.expr_or_error(stmt.scrutinee(), stmt.syntax()),
+                    clauses: self.vec(clauses),
+                }))
+            }
+            ast::Stmt::BreakStmt(stmt) => Stmt::Break(span(stmt.syntax())),
+            ast::Stmt::ContinueStmt(stmt) => Stmt::Continue(span(stmt.syntax())),
+            ast::Stmt::ReturnStmt(stmt) => Stmt::Return(self.boxed(ReturnStmt {
+                span: span(stmt.syntax()),
+                value: stmt.value().map(|value| self.expr(value)),
+            })),
+            ast::Stmt::ThrowStmt(stmt) => Stmt::Throw(self.boxed(ThrowStmt {
+                span: span(stmt.syntax()),
+                value: self.expr_or_error(stmt.value(), stmt.syntax()),
+            })),
+            ast::Stmt::TryStmt(stmt) => {
+                let catch_clauses = stmt.catch_clauses().map(|clause| CatchClause {
+                    span: span(clause.syntax()),
+                    param: clause.param().map(|param| self.param(&param)),
+                    body: self.block_or_empty(clause.body(), clause.syntax()),
+                });
+                Stmt::Try(self.boxed(TryStmt {
+                    span: span(stmt.syntax()),
+                    body: self.block_or_empty(stmt.body(), stmt.syntax()),
+                    catch_clauses: self.vec(catch_clauses),
+                }))
+            }
+            ast::Stmt::UnsafeBlock(stmt) => {
+                Stmt::Unsafe(self.boxed(self.block_or_empty(stmt.block(), stmt.syntax())))
+            }
+            ast::Stmt::Item(item) => Stmt::Item(self.item(item)),
+        }
+    }
+
+    fn if_stmt(&self, stmt: &ast::IfStmt) -> IfStmt<'a> {
+        let else_branch = stmt.else_branch().map(|branch| match branch {
+            ast::ElseBranch::If(stmt) => ElseBranch::If(self.boxed(self.if_stmt(&stmt))),
+            ast::ElseBranch::Block(block) => ElseBranch::Block(self.boxed(self.block(&block))),
+        });
+        IfStmt {
+            span: span(stmt.syntax()),
+            condition: self.expr_or_error(stmt.condition(), stmt.syntax()),
+            then_branch: self.block_or_empty(stmt.then_branch(), stmt.syntax()),
+            else_branch,
+        }
+    }
+
+    fn expr_or_error(&self, expr: Option<ast::Expr>, parent: &SyntaxNode) -> Expr<'a> {
+        match expr {
+            Some(expr) => self.expr(expr),
+            None => Expr::Error(missing(parent)),
+        }
+    }
+
+    fn exprs(&self, exprs: impl Iterator<Item = ast::Expr>) -> Vec<'a, Expr<'a>> {
+        self.vec(exprs.map(|expr| self.expr(expr)))
+    }
+
+    fn expr(&self, expr: ast::Expr) -> Expr<'a> {
+        let node = expr.syntax().clone();
+        match expr {
+            ast::Expr::Literal(literal) => Expr::Literal(self.boxed(Literal {
+                span: span(&node),
+                value: self.literal(&literal),
+            })),
+            ast::Expr::PathExpr(expr) => match expr.path() {
+                Some(path) => Expr::Path(self.boxed(self.path(&path))),
+                None => Expr::Error(span(&node)),
+            },
+            ast::Expr::BinaryExpr(expr) => match expr.op_kind().and_then(binary_op) {
+                Some(op) => Expr::Binary(self.boxed(BinaryExpr {
+                    span: span(&node),
+                    op,
+                    lhs: self.expr_or_error(expr.lhs(), &node),
+                    rhs: self.expr_or_error(expr.rhs(), &node),
+                })),
+                None => Expr::Error(span(&node)),
+            },
+            ast::Expr::PrefixExpr(expr) => match expr.op_kind().and_then(prefix_op) {
+                Some(op) => Expr::Prefix(self.boxed(PrefixExpr {
+                    span: span(&node),
+                    op,
+                    operand: self.expr_or_error(expr.operand(), &node),
+                })),
+                None => Expr::Error(span(&node)),
+            },
+            ast::Expr::TernaryExpr(expr) => Expr::Ternary(self.boxed(TernaryExpr {
+                span: span(&node),
+                condition: self.expr_or_error(expr.condition(), &node),
+                then_expr: self.expr_or_error(expr.then_expr(), &node),
+                else_expr: self.expr_or_error(expr.else_expr(), &node),
+            })),
+            ast::Expr::CallExpr(expr) => Expr::Call(self.boxed(CallExpr {
+                span: span(&node),
+                callee: self.expr_or_error(expr.callee(), &node),
+                args: self.exprs(expr.args()),
+            })),
+            ast::Expr::IndexExpr(expr) => Expr::Index(self.boxed(IndexExpr {
+                span: span(&node),
+                base: self.expr_or_error(expr.base(), &node),
+                index: self.expr_or_error(expr.index(), &node),
+            })),
+            ast::Expr::MemberExpr(expr) => Expr::Member(self.boxed(MemberExpr {
+                span: span(&node),
+                receiver: self.expr_or_error(expr.receiver(), &node),
+                member: self.name(&expr),
+            })),
+            ast::Expr::CastExpr(expr) => Expr::Cast(self.boxed(CastExpr {
+                span: span(&node),
+                expr: self.expr_or_error(expr.expr(), &node),
+                ty: self.ty_or_error(expr.ty(), &node),
+            })),
+            ast::Expr::NewExpr(expr) => Expr::New(self.boxed(NewExpr {
+                span: span(&node),
+                ty: self.ty_or_error(expr.ty(), &node),
+                args: self.exprs(expr.args()),
+            })),
+            ast::Expr::ParenExpr(expr) => self.expr_or_error(expr.expr(), &node),
+        }
+    }
+
+    fn literal(&self, literal: &ast::Literal) -> LiteralValue<'a> {
+        let Some(token) = literal.token() else {
+            return LiteralValue::Invalid;
+        };
+
+        match token.kind().to_token_kind() {
+            Some(TokenKind::ValueInteger | TokenKind::ValueFloat) => {
+                crate::number::evaluate(token.text())
+                    .map_or(LiteralValue::Invalid, LiteralValue::Number)
+            }
+            Some(TokenKind::ValueString) => match cooked(&token) {
+                Some(text) => LiteralValue::String(self.str(&text)),
+                None => LiteralValue::Invalid,
+            },
+            Some(TokenKind::ValueChar) => {
+                let text = cooked(&token).unwrap_or_default();
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => LiteralValue::Char(c),
+                    _ => LiteralValue::Invalid,
+                }
+            }
+            Some(TokenKind::KWordTrue) => LiteralValue::Bool(true),
+            Some(TokenKind::KWordFalse) => LiteralValue::Bool(false),
+            Some(TokenKind::KwordNull) => LiteralValue::Null,
+            _ => LiteralValue::Invalid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::number::NumberValue;
+    use crate::parser::parse;
+
+    fn program<'a>(allocator: &'a Allocator, text: &str) -> Program<'a> {
+        let parse = parse(text);
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        lower(allocator, &parse.tree())
+    }
+
+    #[test]
+    fn func() {
+        let allocator = Allocator::default();
+        let program = program(
+            &allocator,
+            "public static func add(a: i32, b: *mut i32 = 1) i32 { return (a + b) * 2; }",
+        );
+
+        let [Item::Func(func)] = program.items.as_slice() else {
+            panic!("not a single function");
+        };
+        assert_eq!(func.name.unwrap().name, "add");
+        assert!(func
+            .modifiers
+            .contains(Modifiers::PUBLIC | Modifiers::STATIC));
+        assert!(!func.modifiers.contains(Modifiers::VIRTUAL));
+
+        assert_eq!(func.params.len(), 2);
+        let Some(Type::Pointer(pointer)) = &func.params[1].ty else {
+            panic!("not a pointer");
+        };
+        assert_eq!(pointer.kind, PointerKind::Mut);
+        assert!(func.params[1].default_value.is_some());
+
+        let body = func.body.as_ref().unwrap();
+        let [Stmt::Return(ret)] = body.stmts.as_slice() else {
+            panic!("not a single return");
+        };
+        // The parentheses are gone, the sum is the direct operand of the product
+        let Some(Expr::Binary(product)) = &ret.value else {
+            panic!("not a binary expression");
+        };
+        assert_eq!(product.op, BinaryOp::Mul);
+        let Expr::Binary(sum) = &product.lhs else {
+            panic!("not a binary expression");
+        };
+        assert_eq!(sum.op, BinaryOp::Add);
+        assert_eq!(sum.span, Span { start: 62, end: 67 });
+    }
+
+    #[test]
+    fn literals() {
+        let allocator = Allocator::default();
+        let program = program(
+            &allocator,
+            r#"let a = f(42, 1.5, "a\tb", '\n', true, 300u8, null);"#,
+        );
+
+        let [Item::Var(var)] = program.items.as_slice() else {
+            panic!("not a single variable");
+        };
+        assert_eq!(var.kind, VarKind::Let);
+        assert_eq!(var.name.unwrap().name, "a");
+        let Some(Expr::Call(call)) = &var.initializer else {
+            panic!("not a call");
+        };
+
+        let values = call
+            .args
+            .iter()
+            .map(|arg| match arg {
+                Expr::Literal(literal) => literal.value,
+                _ => panic!("not a literal"),
+            })
+            .collect::<std::vec::Vec<_>>();
+
+        let LiteralValue::Number(number) = values[0] else {
+            panic!("not a number");
+        };
+        assert_eq!(number.value, NumberValue::Signed(42));
+        let LiteralValue::Number(number) = values[1] else {
+            panic!("not a number");
+        };
+        assert_eq!(number.value, NumberValue::Float(1.5));
+        assert_eq!(values[2], LiteralValue::String("a\tb"));
+        assert_eq!(values[3], LiteralValue::Char('\n'));
+        assert_eq!(values[4], LiteralValue::Bool(true));
+        let LiteralValue::Number(number) = values[5] else {
+            panic!("not a number");
+        };
+        assert!(number.diagnostic.is_some());
+        assert_eq!(values[6], LiteralValue::Null);
+    }
+
+    #[test]
+    fn declarations() {
+        let allocator = Allocator::default();
+        let program = program(
+            &allocator,
+            r#"
+            namespace std::io {
+                class File extends Stream implements Read, Write {
+                    path: [u8];
+                    const mut size: u64 = 0;
+                }
+                enum Mode { Read, Write(i32) = 2 }
+                import std::fs as fs;
+                struct Stat { mode: *const Mode }
+            }
+            "#,
+        );
+
+        let [Item::Namespace(namespace)] = program.items.as_slice() else {
+            panic!("not a single namespace");
+        };
+        let path = namespace.path.as_ref().unwrap();
+        let segments = path
+            .segments
+            .iter()
+            .map(|segment| segment.name.name)
+            .collect::<std::vec::Vec<_>>();
+        assert_eq!(segments, ["std", "io"]);
+
+        let [Item::Class(class), Item::Enum(enm), Item::Import(import), Item::Struct(stat)] =
+            namespace.items.as_slice()
+        else {
+            panic!("not the declarations in the namespace");
+        };
+
+        assert_eq!(class.extends.len(), 1);
+        assert_eq!(class.implements.len(), 2);
+        let [Item::Field(field), Item::Var(var)] = class.members.as_slice() else {
+            panic!("not a field and a constant");
+        };
+        assert!(matches!(field.ty, Type::Array(_)));
+        assert_eq!(var.kind, VarKind::Const);
+        assert!(var.is_mut);
+
+        assert_eq!(enm.variants.len(), 2);
+        assert_eq!(enm.variants[1].fields.len(), 1);
+        assert!(enm.variants[1].value.is_some());
+
+        assert_eq!(import.alias.unwrap().name, "fs");
+        let [Item::Field(field)] = stat.members.as_slice() else {
+            panic!("not a single field");
+        };
+        assert!(matches!(&field.ty, Type::Pointer(ty) if ty.kind == PointerKind::Const));
+    }
+
+    #[test]
+    fn generics() {
+        let allocator = Allocator::default();
+        let program = program(&allocator, "func first<T>(v: Map<str, Vec<T>>) T;");
+
+        let [Item::Func(func)] = program.items.as_slice() else {
+            panic!("not a single function");
+        };
+        assert_eq!(func.generic_params.len(), 1);
+        assert_eq!(func.generic_params[0].name, "T");
+
+        let Some(Type::Path(map)) = &func.params[0].ty else {
+            panic!("not a path type");
+        };
+        let [segment] = map.segments.as_slice() else {
+            panic!("not a single segment");
+        };
+        assert_eq!(segment.name.name, "Map");
+        let [_, Type::Path(vec)] = segment.generic_args.as_slice() else {
+            panic!("not two arguments");
+        };
+        assert_eq!(vec.segments[0].generic_args.len(), 1);
+    }
+
+    #[test]
+    fn missing_parts() {
+        let allocator = Allocator::default();
+        let parse = parse("func (a: ) { let x = 1 + ; if { } }");
+        assert!(!parse.errors.is_empty());
+        let program = lower(&allocator, &parse.tree());
+
+        let [Item::Func(func)] = program.items.as_slice() else {
+            panic!("not a single function");
+        };
+        assert!(func.name.is_none());
+        assert!(func.params[0].ty.is_none());
+
+        let stmts = &func.body.as_ref().unwrap().stmts;
+        let Stmt::Var(var) = &stmts[0] else {
+            panic!("not a variable");
+        };
+        let Some(Expr::Binary(sum)) = &var.initializer else {
+            panic!("not a binary expression");
+        };
+        assert!(matches!(sum.rhs, Expr::Error(_)));
+
+        let Stmt::If(if_stmt) = &stmts[1] else {
+            panic!("not an if");
+        };
+        assert!(matches!(if_stmt.condition, Expr::Error(_)));
+    }
+}
@@ -0,0 +1,237 @@
+//! The keywords of the language
+//!
+//! Everything about a keyword is written down once, in the list at the bottom of this file. The
+//! lexer looks words up in it through a perfect hash built at compile time, [`TokenKind::text`]
+//! spells keywords out from it, and [`KEYWORDS`] hands it to anything that wants every keyword,
+//! like completion or the syntax-highlighting grammars.
+//!
+//! Words only become keywords in the edition they were added in, so code written for an older
+//! edition can keep using them as names. Contextual keywords are never taken away from names: the
+//! lexer still reports them as keywords, and the parser reads them as identifiers wherever a
+//! keyword could not go, like the `final` in `final = true;`.
+
+use crate::ast::TokenKind;
+
+/// Whether a keyword can be used as a name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordClass {
+    /// Always a keyword, only usable as a name written as a raw identifier, like `r#class`
+    Reserved,
+    /// Only means something in a few places, like the modifiers before a declaration
+    Contextual,
+}
+
+/// A version of the language, which decides what words are keywords
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Edition {
+    /// The first keywords, before `null`, `in`, `yield`, `async`, `this` and `self`
+    Edition2023,
+    #[default]
+    Edition2024,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keyword {
+    pub text: &'static str,
+    pub kind: TokenKind,
+    pub class: KeywordClass,
+    /// The edition the word became a keyword in
+    pub since: Edition,
+}
+
+/// The keyword spelled `text` in any edition, if it is one
+pub fn lookup(text: &str) -> Option<&'static Keyword> {
+    LOOKUP.get(text)
+}
+
+/// The keyword spelled `text` in `edition`, words that only become keywords later are names
+pub fn lookup_in(text: &str, edition: Edition) -> Option<&'static Keyword> {
+    lookup(text).filter(|keyword| keyword.since <= edition)
+}
+
+macro_rules! keywords {
+    ($($text:tt => $kind:ident, $class:ident $(, since $since:ident)?;)*) => {
+        /// Every keyword, in no particular order
+        pub static KEYWORDS: &[Keyword] = &[$(keyword!($text, $kind, $class $(, $since)?),)*];
+
+        static LOOKUP: phf::Map<&'static str, Keyword> = phf::phf_map! {
+            $($text => keyword!($text, $kind, $class $(, $since)?),)*
+        };
+
+        /// The text of a keyword kind, [`None`] for other kinds
+        pub(crate) fn text(kind: TokenKind) -> Option<&'static str> {
+            match kind {
+                $(TokenKind::$kind => Some($text),)*
+                _ => None,
+            }
+        }
+
+        /// The class of a keyword kind, [`None`] for other kinds
+        pub fn class(kind: TokenKind) -> Option<KeywordClass> {
+            match kind {
+                $(TokenKind::$kind => Some(KeywordClass::$class),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+macro_rules! keyword {
+    ($text:tt, $kind:ident, $class:ident) => {
+        keyword!($text, $kind, $class, Edition2023)
+    };
+    ($text:tt, $kind:ident, $class:ident, $since:ident) => {
+        Keyword {
+            text: $text,
+            kind: TokenKind::$kind,
+            class: KeywordClass::$class,
+            since: Edition::$since,
+        }
+    };
+}
+
+keywords! {
+    "true" => KWordTrue, Reserved;
+    "false" => KWordFalse, Reserved;
+    "null" => KwordNull, Reserved, since Edition2024;
+    "if" => KwordIf, Reserved;
+    "else" => KwordElse, Reserved;
+    "let" => KwordLet, Reserved;
+    "const" => KwordConst, Reserved;
+    "constexpr" => KwordConstexpr, Reserved;
+    "mut" => KwordMutable, Reserved;
+    "new" => KwordNew, Reserved;
+    "delete" => KwordDelete, Reserved;
+    "for" => KwordFor, Reserved;
+    "in" => KwordIn, Reserved, since Edition2024;
+    "while" => KwordWhile, Reserved;
+    "do" => KwordDo, Reserved;
+    "break" => KwordBreak, Reserved;
+    "continue" => KwordContinue, Reserved;
+    "return" => KwordReturn, Reserved;
+    "yield" => KwordYield, Reserved, since Edition2024;
+    "switch" => KwordSwitch, Reserved;
+    "case" => KwordCase, Reserved;
+    "default" => KwordDefault, Contextual;
+    "throw" => KwordThrow, Reserved;
+    "try" => KwordTry, Reserved;
+    "catch" => KwordCatch, Reserved;
+    "unsafe" => KwordUnsafe, Reserved;
+    "as" => KwordAs, Reserved;
+    "func" => KwordFunc, Reserved;
+    "operator" => KwordOperator, Contextual;
+    "macro" => KwordMacro, Reserved;
+    "enum" => KwordEnum, Reserved;
+    "class" => KwordClass, Reserved;
+    "struct" => KwordStruct, Reserved;
+    "interface" => KwordInter, Reserved;
+    "namespace" => KwordNamespace, Reserved;
+    "type" => KwordTypedef, Reserved;
+    "import" => KwordImport, Reserved;
+    "extern" => KwordExtern, Reserved;
+    "extends" => KwordExtends, Contextual;
+    "implements" => KwordImplements, Contextual;
+    "public" => KwordPublic, Reserved;
+    "private" => KwordPrivate, Reserved;
+    "static" => KwordStatic, Reserved;
+    "virtual" => KwordVirtual, Contextual;
+    "override" => KwordOverride, Contextual;
+    "abstract" => KwordAbstract, Contextual;
+    "final" => KwordFinal, Contextual;
+    "inline" => KwordInline, Contextual;
+    "async" => KwordAsync, Contextual, since Edition2024;
+    "this" => KwordThis, Reserved, since Edition2024;
+    "self" => KwordSelf, Reserved, since Edition2024;
+    "super" => KwordSuper, Reserved;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lexer::{Lexer, LexerOptions};
+    use crate::parser::{parse, parse_with_options};
+
+    fn kinds(text: &str, edition: Edition) -> Vec<TokenKind> {
+        let options = LexerOptions {
+            edition,
+            ..LexerOptions::default()
+        };
+        Lexer::with_options(text, options)
+            .map(|tok| tok.kind)
+            .filter(|kind| *kind != TokenKind::Whitespace)
+            .collect()
+    }
+
+    #[test]
+    fn every_keyword_lexes_to_its_kind() {
+        for keyword in KEYWORDS {
+            let kinds = Lexer::new(keyword.text)
+                .map(|tok| tok.kind)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                kinds,
+                vec![keyword.kind, TokenKind::Eof],
+                "{}",
+                keyword.text
+            );
+
+            assert_eq!(lookup(keyword.text), Some(keyword));
+            assert_eq!(keyword.kind.text(), Some(keyword.text));
+        }
+    }
+
+    #[test]
+    fn keywords_are_unique() {
+        for (i, keyword) in KEYWORDS.iter().enumerate() {
+            for other in &KEYWORDS[i + 1..] {
+                assert_ne!(keyword.text, other.text);
+                assert_ne!(keyword.kind, other.kind);
+            }
+        }
+    }
+
+    #[test]
+    fn other_words_are_not_keywords() {
+        for text in [
+            "external", "var", "Class", "classes", "r#class", "", "$this",
+        ] {
+            assert_eq!(lookup(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn newer_keywords_are_names_in_older_editions() {
+        assert_eq!(
+            kinds("this null func", Edition::Edition2023),
+            [
+                TokenKind::Identifier,
+                TokenKind::Identifier,
+                TokenKind::KwordFunc,
+                TokenKind::Eof,
+            ]
+        );
+        assert_eq!(
+            kinds("this null func", Edition::Edition2024),
+            [
+                TokenKind::KwordThis,
+                TokenKind::KwordNull,
+                TokenKind::KwordFunc,
+                TokenKind::Eof,
+            ]
+        );
+
+        assert_eq!(lookup_in("self", Edition::Edition2023), None);
+        assert_eq!(
+            lookup_in("super", Edition::Edition2023).map(|k| k.kind),
+            Some(TokenKind::KwordSuper)
+        );
+
+        let text = "func f(in: i32) { let self = in; }";
+        let options = LexerOptions {
+            edition: Edition::Edition2023,
+            ..LexerOptions::default()
+        };
+        assert!(parse_with_options(text, options).errors.is_empty());
+        assert!(!parse(text).errors.is_empty());
+    }
+}
@@ -1,31 +1,44 @@
 // We use the `BYTE_HANDLERS` table for our parsing, which is just a table of functions
 // that correspond to a specific ascii byte value.
 //
-// Current challenges:
-// - For generics, you would need to be able to handle `>>` as `OpBitLshift`, this complicates
-//   things, and we may just have to insert two `OpGt` tokens instead. This would just be messier
-//   and I would hope to just have the generics part of the parsing be able to just "figure it out"
-
-use crate::ast::{Token, TokenKind};
+// Generics:
+// - `Foo<Bar<Baz>>` lexes its `>>` as `OpBitRshift`, the lexer can't tell it apart from a shift.
+//   Instead the parser splits `>>`, `>>=` and `>=` on demand when one of them closes a generic
+//   list, see `Parser::eat_gt`.
+//
+// Handlers never look back at what came before the token they lex, which is what lets `relex`
+// pick the lexing up again in the middle of a text.
+
+use crate::ast::{Span, Token, TokenKind};
+use crate::error::{LexError, LexErrorKind};
+use crate::escape::scan_escape;
+use crate::keywords::{self, Edition};
+use crate::number::NumberSuffix;
 use crate::source::Source;
 
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+mod relex;
+
+pub use relex::{relex, Relexed};
+
 /// Function that handles a specific byte value
-pub type ByteHandler = Option<for<'alloc> fn(&mut Lexer)>;
+pub type ByteHandler = Option<for<'a> fn(&mut Lexer<'a>)>;
 
 /// List of byte handlers for each byte value.
 /// Ref: <https://www.freecodecamp.org/news/ascii-table-hex-to-ascii-value-character-code-chart-2/>
 #[rustfmt::skip]
 pub static BYTE_HANDLERS: [ByteHandler; 256] = [
 //   0    1    2    3    4    5    6    7    8    9    A    B    C    D    E    F   //
-    EOF, ___, ___, ___, ___, ___, ___, ___, ___, SPS, LNN, ___, ___, ___, ___, ___, // 0
+    ___, ___, ___, ___, ___, ___, ___, ___, ___, SPS, LNN, SPS, SPS, LNN, ___, ___, // 0
     ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, // 1
     SPS, OEM, STR, SHT, IDN, OMD, OAD, CHR, SLP, SRP, OSR, OPS, SCM, OMS, SDT, ODV, // 2
     NUM, NUM, NUM, NUM, NUM, NUM, NUM, NUM, NUM, NUM, SAC, SBC, OLT, OEQ, OGT, SQM, // 3
     SAT, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, // 4
-    IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, SLB, SRB, ___, ___, ___, // 5
-    ___, LLA, LLB, LLC, LLD, LLE, LLF, IDN, IDN, LLI, IDN, IDN, LLL, LLM, LLN, LLO, // 6
-    LLP, IDN, LLR, LLS, LLT, LLU, LLV, LLW, IDN, IDN, IDN, SLC, OVB, SRC, OTE, ___, // 7
-    UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, OCT, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // 8
+    IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, SLB, ___, SRB, OCT, UND, // 5
+    ___, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, // 6
+    IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, IDN, SLC, OVB, SRC, OTE, ___, // 7
+    UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // 8
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // 9
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // A
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // B
@@ -35,23 +48,37 @@ pub static BYTE_HANDLERS: [ByteHandler; 256] = [
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // F
 ];
 
-/// Identifiers, and special characters like `$` and `_`
+/// Identifiers that start with an ASCII letter or `$`, and keywords, see `crate::keywords`
 pub const IDN: ByteHandler = Some(|lex| {
-    lex.identifier_handler();
-    // println!("identifier");
-
-    lex.token.kind = TokenKind::Identifier;
+    lex.token.kind = match lex.identifier_handler() {
+        "r" if lex.at_raw_identifier() => {
+            lex.bump();
+            lex.identifier_handler();
+            TokenKind::Identifier
+        }
+        text => keywords::lookup_in(text, lex.options.edition)
+            .map_or(TokenKind::Identifier, |keyword| keyword.kind),
+    };
 });
 
-/// Line new (\n)
+/// Line new (\n, \r\n or a lone \r)
 pub const LNN: ByteHandler = Some(|lex| {
+    let line_ending = if lex.read_byte() == b'\n' {
+        LineEnding::Lf
+    } else if lex.peek_byte() == Some(b'\n') {
+        lex.bump();
+        LineEnding::CrLf
+    } else {
+        LineEnding::Cr
+    };
     lex.bump();
+
+    lex.line_ending_handler(line_ending);
     lex.token.kind = TokenKind::Newline;
 });
 
 pub const NUM: ByteHandler = Some(|lex| {
-    lex.number_handler();
-    lex.token.kind = TokenKind::ValueNumber;
+    lex.token.kind = lex.number_handler();
 });
 
 pub const STR: ByteHandler = Some(|lex| {
@@ -72,11 +99,6 @@ pub const SPS: ByteHandler = Some(|lex| {
     lex.token.kind = TokenKind::Whitespace;
 });
 
-/// The... end... of... the.. file?
-pub const EOF: ByteHandler = Some(|_lex| {
-    println!("End of file reached");
-});
-
 /// Symbol `#`
 pub const SHT: ByteHandler = Some(|lex| {
     lex.token.kind = TokenKind::SymHash;
@@ -110,7 +132,7 @@ pub const SDT: ByteHandler = Some(|lex| {
 /// Symbol ALPHA `:` (alpha as in alpha male)
 pub const SAC: ByteHandler = Some(|lex| {
     // lex.token.kind = TokenKind::SymColon;
-    if lex.read_byte() == b':' {
+    if lex.peek_byte() == Some(b':') {
         lex.token.kind = TokenKind::SymColcol;
         lex.bump();
     } else {
@@ -164,7 +186,7 @@ pub const SRB: ByteHandler = Some(|lex| {
 /// Operator `!` (exclamation mark)
 pub const OEM: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpNot;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpNoteq;
         lex.bump();
     } else {
@@ -176,7 +198,7 @@ pub const OEM: ByteHandler = Some(|lex| {
 /// Operator `*` (asterisk/star)
 pub const OSR: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpMul;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpMuleq;
         lex.bump();
     } else {
@@ -188,7 +210,7 @@ pub const OSR: ByteHandler = Some(|lex| {
 /// Operator `+` (plus)
 pub const OPS: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpPlus;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpPluseq;
         lex.bump();
     } else {
@@ -200,10 +222,10 @@ pub const OPS: ByteHandler = Some(|lex| {
 /// Operator `-` (minus)
 pub const OMS: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpMinus;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpMinuseq;
         lex.bump();
-    } else if lex.read_byte() == b'>' {
+    } else if lex.peek_byte() == Some(b'>') {
         lex.token.kind = TokenKind::OpArrow;
         lex.bump();
     } else {
@@ -215,7 +237,7 @@ pub const OMS: ByteHandler = Some(|lex| {
 /// Operator `%` (percent/mod)
 pub const OMD: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpMod;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpModEq;
         lex.bump();
     } else {
@@ -227,10 +249,10 @@ pub const OMD: ByteHandler = Some(|lex| {
 /// Operator `&` (ampersand/and)
 pub const OAD: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpAnd;
-    if lex.read_byte() == b'&' {
+    if lex.peek_byte() == Some(b'&') {
         lex.token.kind = TokenKind::OpAnd;
         lex.bump();
-    } else if lex.read_byte() == b'=' {
+    } else if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpBitAndEq;
         lex.bump();
     } else {
@@ -239,28 +261,47 @@ pub const OAD: ByteHandler = Some(|lex| {
     lex.bump();
 });
 
-/// Operator `/` (slash/div)
+/// Operator `/` (slash/div), or the start of a line/block comment
 pub const ODV: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpDiv;
-    if lex.read_byte() == b'=' {
-        lex.token.kind = TokenKind::OpDiveq;
-        lex.bump();
-    } else {
-        lex.token.kind = TokenKind::OpDiv;
+    match lex.peek_byte() {
+        Some(b'/') => {
+            let comment = lex.line_comment_handler();
+            lex.token.kind = if is_doc_line_comment(comment) {
+                TokenKind::DocLineComment
+            } else {
+                TokenKind::LineComment
+            };
+        }
+        Some(b'*') => {
+            let comment = lex.block_comment_handler();
+            lex.token.kind = if is_doc_block_comment(comment) {
+                TokenKind::DocBlockComment
+            } else {
+                TokenKind::BlockComment
+            };
+        }
+        Some(b'=') => {
+            lex.token.kind = TokenKind::OpDiveq;
+            lex.bump();
+            lex.bump();
+        }
+        _ => {
+            lex.token.kind = TokenKind::OpDiv;
+            lex.bump();
+        }
     }
-    lex.bump();
 });
 
 /// Operator `<` (less than)
 pub const OLT: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpLt;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpLteq;
         lex.bump();
-    } else if lex.read_byte() == b'<' {
-        // lex.token.kind = TokenKind::OpBitRshift;
-        //lex.bump();
-        if lex.read_byte() == b'=' {
+    } else if lex.peek_byte() == Some(b'<') {
+        lex.bump();
+        if lex.peek_byte() == Some(b'=') {
             lex.token.kind = TokenKind::OpBitLshiftEq;
             lex.bump();
         } else {
@@ -275,7 +316,7 @@ pub const OLT: ByteHandler = Some(|lex| {
 /// Operator `=` (equals)
 pub const OEQ: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpEq;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpEqeq;
         lex.bump();
     } else {
@@ -287,13 +328,13 @@ pub const OEQ: ByteHandler = Some(|lex| {
 /// Operator `>` (greater than)
 pub const OGT: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpGt;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpGteq;
         lex.bump();
-    } else if lex.read_byte() == b'>' {
+    } else if lex.peek_byte() == Some(b'>') {
         // lex.token.kind = TokenKind::OpBitRshift;
         lex.bump();
-        if lex.read_byte() == b'=' {
+        if lex.peek_byte() == Some(b'=') {
             lex.token.kind = TokenKind::OpBitRshiftEq;
             lex.bump();
         } else {
@@ -308,10 +349,10 @@ pub const OGT: ByteHandler = Some(|lex| {
 /// Operator `|` (vertical bar)
 pub const OVB: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpBitOr;
-    if lex.read_byte() == b'|' {
+    if lex.peek_byte() == Some(b'|') {
         lex.token.kind = TokenKind::OpOr;
         lex.bump();
-    } else if lex.read_byte() == b'=' {
+    } else if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpBitOrEq;
         lex.bump();
     } else {
@@ -329,7 +370,7 @@ pub const OTE: ByteHandler = Some(|lex| {
 /// Operator `^` (caret)
 pub const OCT: ByteHandler = Some(|lex| {
     //lex.token.kind = TokenKind::OpBitXor;
-    if lex.read_byte() == b'=' {
+    if lex.peek_byte() == Some(b'=') {
         lex.token.kind = TokenKind::OpBitXorEq;
         lex.bump();
     } else {
@@ -338,216 +379,143 @@ pub const OCT: ByteHandler = Some(|lex| {
     lex.bump();
 });
 
-/// Literal lowercase `a`
-pub const LLA: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "as" => TokenKind::KwordAs,
-        "abstract" => TokenKind::KwordAbstract,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `b`
-pub const LLB: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "break" => TokenKind::KwordBreak,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `c`
-pub const LLC: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "case" => TokenKind::KwordCase,
-        "const" => TokenKind::KwordConst,
-        "continue" => TokenKind::KwordContinue,
-        "class" => TokenKind::KwordClass,
-        "catch" => TokenKind::KwordCatch,
-        "constexpr" => TokenKind::KwordConstexpr,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `d`
-pub const LLD: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "do" => TokenKind::KwordDo,
-        "default" => TokenKind::KwordDefault,
-        "delete" => TokenKind::KwordDelete,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `e`
-pub const LLE: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "enum" => TokenKind::KwordEnum,
-        "else" => TokenKind::KwordElse,
-        "extends" => TokenKind::KwordExtends,
-        "external" => TokenKind::KwordExtern,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `f`
-pub const LLF: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "for" => TokenKind::KwordFor,
-        "false" => TokenKind::KWordFalse,
-        "final" => TokenKind::KwordFinal,
-        "func" => TokenKind::KwordFunc,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `i`
-pub const LLI: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "if" => TokenKind::KwordIf,
-        "interface" => TokenKind::KwordInter,
-        "implements" => TokenKind::KwordImplements,
-        "import" => TokenKind::KwordImport,
-        "inline" => TokenKind::KwordInline,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `l`
-pub const LLL: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "let" => TokenKind::KwordVar,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `m`
-pub const LLM: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "macro" => TokenKind::KwordMacro,
-        "mut" => TokenKind::KwordMutable,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `n`
-pub const LLN: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "namespace" => TokenKind::KwordNamespace,
-        "new" => TokenKind::KwordNew,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `o`
-pub const LLO: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "operator" => TokenKind::KwordOperator,
-        "override" => TokenKind::KwordOverride,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `p`
-pub const LLP: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "public" => TokenKind::KwordPublic,
-        "private" => TokenKind::KwordPrivate,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `r`
-pub const LLR: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "return" => TokenKind::KwordReturn,
-        _ => TokenKind::Identifier,
-    };
-});
-
-/// Literal lowercase `s`
-pub const LLS: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "super" => TokenKind::KwordSuper,
-        "static" => TokenKind::KwordStatic,
-        "struct" => TokenKind::KwordStruct,
-        "switch" => TokenKind::KwordSwitch,
-        _ => TokenKind::Identifier,
-    };
-});
+/// Bytes outside of ASCII, the start of a multi-byte UTF-8 character
+pub const UNI: ByteHandler = Some(|lex| {
+    if utf8_len(lex.read_byte()).is_none() {
+        // Only reachable if a handler stopped in the middle of a character
+        lex.bump();
+        lex.error_from(LexErrorKind::InvalidUtf8, lex.token.span.start);
+        lex.token.kind = TokenKind::Error;
+        return;
+    }
 
-/// Literal lowercase `t`
-pub const LLT: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "true" => TokenKind::KWordTrue,
-        "type" => TokenKind::KwordTypedef,
-        "throw" => TokenKind::KwordThrow,
-        "try" => TokenKind::KwordTry,
-        _ => TokenKind::Identifier,
-    };
+    match lex.current_char() {
+        Some(c) if is_xid_start(c) => {
+            lex.identifier_handler();
+            lex.token.kind = TokenKind::Identifier;
+        }
+        Some(c) => {
+            lex.bump_char();
+            lex.error_from(LexErrorKind::UnexpectedChar(c), lex.token.span.start);
+            lex.token.kind = TokenKind::Error;
+        }
+        None => unreachable!("UNI is only dispatched for a byte that is there"),
+    }
 });
 
-/// Literal lowercase `u`
-pub const LLU: ByteHandler = Some(|lex| {
+/// Symbol `_` on its own, and otherwise the start of an identifier
+pub const UND: ByteHandler = Some(|lex| {
     lex.token.kind = match lex.identifier_handler() {
-        "unsafe" => TokenKind::KwordUnsafe,
+        "_" => TokenKind::Underscore,
         _ => TokenKind::Identifier,
     };
 });
 
-/// Literal lowercase `v`
-pub const LLV: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "virtual" => TokenKind::KwordVirtual,
-        _ => TokenKind::Identifier,
-    };
-});
+pub const ___: ByteHandler = None;
 
-/// Literal lowercase `w`
-pub const LLW: ByteHandler = Some(|lex| {
-    lex.token.kind = match lex.identifier_handler() {
-        "while" => TokenKind::KwordWhile,
-        _ => TokenKind::Identifier,
-    };
-});
+/// Settings that change what the lexer reports
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LexerOptions {
+    /// Report every line ending that differs from the first one in the file
+    pub report_mixed_line_endings: bool,
+    /// Which words are keywords
+    pub edition: Edition,
+}
 
-pub const UNI: ByteHandler = Some(|lex| {
-    // lex.source.advance(1);
-    lex.bump();
-    // TODO: Whatever this is
-});
+/// The different ways a line can end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// A lone `\r`
+    Cr,
+}
 
-pub const ___: ByteHandler = None;
+impl std::fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineEnding::Lf => write!(f, "LF"),
+            LineEnding::CrLf => write!(f, "CRLF"),
+            LineEnding::Cr => write!(f, "CR"),
+        }
+    }
+}
 
-pub struct Lexer {
+pub struct Lexer<'a> {
     // allocator: &'alloc Allocator,
-    source: Source,
+    source: Source<'a>,
     token: Token,
+    errors: Vec<LexError>,
+    options: LexerOptions,
+    /// The first line ending seen, to compare the rest against
+    line_ending: Option<LineEnding>,
+    /// Whether the iterator has handed out the `Eof` token
+    finished: bool,
 }
 
-impl Lexer {
-    pub fn new(input: &str) -> Lexer {
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Lexer<'a> {
+        Lexer::with_options(input, LexerOptions::default())
+    }
+
+    pub fn with_options(input: &'a str, options: LexerOptions) -> Lexer<'a> {
         Lexer {
             source: Source::new(input),
             token: Token::default(),
+            errors: vec![],
+            options,
+            line_ending: None,
+            finished: false,
         }
     }
 
+    /// The whole input this lexer was created with
+    pub fn source_text(&self) -> &'a str {
+        self.source.get_whole_source()
+    }
+
+    /// Errors found so far, in the order they were found
+    pub fn errors(&self) -> &[LexError] {
+        &self.errors
+    }
+
+    /// Takes the errors found so far out of the lexer
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        std::mem::take(&mut self.errors)
+    }
+
     pub fn is_at_end(&self) -> bool {
         self.source.is_at_end()
     }
 
+    /// Lexes the next token. Once the end of the input is reached, this keeps returning an empty
+    /// [`TokenKind::Eof`] token
     pub fn next_token(&mut self) -> Token {
+        self.token.span.start = self.source.current_pos();
+
+        if self.is_at_end() {
+            self.token.kind = TokenKind::Eof;
+            self.token.span.end = self.token.span.start;
+            return std::mem::take(&mut self.token);
+        }
+
         let next_byte = self.read_byte();
 
         if let Some(handler) = self.handler_from_byte(next_byte) {
-            self.token.span.start = self.source.current_pos();
             handler(self);
         } else {
-            println!("Unexpected byte: {} [{}]", next_byte as char, next_byte);
             self.bump();
+            self.error_from(LexErrorKind::StrayByte(next_byte), self.token.span.start);
+            self.token.kind = TokenKind::Error;
         }
 
         self.token.span.end = self.source.current_pos();
+        debug_assert!(
+            self.token.span.end > self.token.span.start,
+            "every token but `Eof` has to consume something"
+        );
 
         let tok = self.token;
         self.token = Token::default();
@@ -559,38 +527,204 @@ impl Lexer {
         self.source.current()
     }
 
+    /// Look at the byte after the current one, without consuming anything
+    fn peek_byte(&self) -> Option<u8> {
+        self.source.peek()
+    }
+
+    /// Decodes the character at the current position
+    ///
+    /// The current position must be on a character boundary
+    fn current_char(&self) -> Option<char> {
+        self.source.remaining().chars().next()
+    }
+
     fn handler_from_byte(&self, byte: u8) -> ByteHandler {
-        unsafe { *(&BYTE_HANDLERS as *const ByteHandler).offset(byte as isize) }
+        // A `u8` can't be out of bounds of a 256 entry table, so this check compiles away
+        BYTE_HANDLERS[byte as usize]
     }
 
     #[inline]
     fn bump(&mut self) {
         self.source.advance_ptr();
     }
+
+    /// Records an error spanning from `start` up to the current position
+    fn error_from(&mut self, kind: LexErrorKind, start: usize) {
+        let span = Span {
+            start,
+            end: self.source.current_pos(),
+        };
+        self.errors.push(LexError::new(kind, span));
+    }
+}
+
+/// Length of the UTF-8 sequence started by `byte`, or [`None`] if it can't start one
+fn utf8_len(byte: u8) -> Option<usize> {
+    match byte {
+        0x00..=0x7F => Some(1),
+        0xC2..=0xDF => Some(2),
+        0xE0..=0xEF => Some(3),
+        0xF0..=0xF4 => Some(4),
+        _ => None,
+    }
+}
+
+/// Iterates over every token, ending with exactly one [`TokenKind::Eof`]
+///
+/// The tokens are lossless: their spans are back to back, and together cover the whole input.
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if self.finished {
+            return None;
+        }
+
+        let tok = self.next_token();
+        self.finished = tok.kind == TokenKind::Eof;
+
+        Some(tok)
+    }
 }
 
 // Identifiers
-impl Lexer {
-    pub(super) fn identifier_handler<'a>(&mut self) -> &'a str {
+//
+// They follow UAX #31, with `_` and `$` added:
+//
+//   identifier     = ["r#"] ident_start {ident_continue}
+//   ident_start    = XID_Start | "_" | "$"
+//   ident_continue = XID_Continue | "$"
+//
+// A lone `_` is not an identifier but the `Underscore` wildcard. `$` is there for the names of
+// `extern` symbols, which some C compilers allow it in. Raw identifiers, `r#` followed by any
+// identifier or keyword, are identifiers that mean the name without the `r#`, so keywords can be
+// used as names, see `crate::ast::identifier_name`.
+impl<'a> Lexer<'a> {
+    /// Consumes an identifier: the first character was already checked by whoever dispatched
+    /// here, and the rest are `ident_continue` characters
+    pub(super) fn identifier_handler(&mut self) -> &'a str {
         let start = self.source.current_pos();
 
+        self.bump_char();
+
         while !self.is_at_end() {
             let byte = self.read_byte();
 
-            if byte.is_ascii_alphanumeric() && (byte as char) != ' ' || byte == b'_' {
+            if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$' {
                 self.bump();
+            } else if byte.is_ascii() {
+                break;
             } else {
+                match self.current_char() {
+                    Some(c) if is_xid_continue(c) => self.bump_char(),
+                    _ => break,
+                }
+            }
+        }
+
+        self.source.get_slice(start, self.source.get_current_pos())
+    }
+
+    /// Whether the `r` just consumed starts a raw identifier, that is, whether it is followed
+    /// by a `#` and an `ident_start` character
+    fn at_raw_identifier(&self) -> bool {
+        if self.is_at_end() || self.read_byte() != b'#' {
+            return false;
+        }
+
+        match self.source.remaining()[1..].chars().next() {
+            Some(c) => c == '_' || c == '$' || is_xid_start(c),
+            None => false,
+        }
+    }
+}
+
+// Newlines
+impl<'a> Lexer<'a> {
+    fn line_ending_handler(&mut self, line_ending: LineEnding) {
+        match self.line_ending {
+            None => self.line_ending = Some(line_ending),
+            Some(first) if first != line_ending && self.options.report_mixed_line_endings => {
+                self.error_from(
+                    LexErrorKind::MixedLineEndings {
+                        expected: first,
+                        found: line_ending,
+                    },
+                    self.token.span.start,
+                );
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Whether `byte` ends a line, either as `\n` or the `\r` of `\r\n`/a lone `\r`
+fn is_line_break(byte: u8) -> bool {
+    byte == b'\n' || byte == b'\r'
+}
+
+// Comments
+impl<'a> Lexer<'a> {
+    /// Consumes a `//` comment up to (but not including) the line break
+    pub(super) fn line_comment_handler(&mut self) -> &'a str {
+        let start = self.source.current_pos();
+
+        // move past the `//`
+        self.bump();
+        self.bump();
+
+        while !self.is_at_end() && !is_line_break(self.read_byte()) {
+            self.bump();
+        }
+
+        self.source.get_slice(start, self.source.get_current_pos())
+    }
+
+    /// Consumes a `/* */` comment. Block comments nest, so `/* /* */ */` is one comment
+    pub(super) fn block_comment_handler(&mut self) -> &'a str {
+        let start = self.source.current_pos();
+        let mut depth = 0usize;
+
+        while !self.is_at_end() {
+            let byte = self.read_byte();
+
+            if byte == b'/' && self.peek_byte() == Some(b'*') {
+                depth += 1;
+                self.bump();
+            } else if byte == b'*' && self.peek_byte() == Some(b'/') {
+                depth -= 1;
+                self.bump();
+            }
+
+            self.bump();
+
+            if depth == 0 {
                 break;
             }
         }
 
-        &self.source.get_slice(start, self.source.get_current_pos())
+        if depth != 0 {
+            self.error_from(LexErrorKind::UnterminatedBlockComment, start);
+        }
+
+        self.source.get_slice(start, self.source.get_current_pos())
     }
 }
 
+/// `///` starts a doc comment, but `////` (and longer) is a plain comment again
+fn is_doc_line_comment(comment: &str) -> bool {
+    comment.starts_with("///") && !comment.starts_with("////")
+}
+
+/// `/**` starts a doc comment, except for `/***` and the empty `/**/`
+fn is_doc_block_comment(comment: &str) -> bool {
+    comment.starts_with("/**") && !comment.starts_with("/***") && comment != "/**/"
+}
+
 // Whitespace
-impl Lexer {
-    pub(super) fn whitespace_handler<'a>(&mut self) -> &'a str {
+impl<'a> Lexer<'a> {
+    pub(super) fn whitespace_handler(&mut self) -> &'a str {
         let start = self.source.current_pos();
 
         while !self.is_at_end() {
@@ -603,88 +737,957 @@ impl Lexer {
             }
         }
 
-        &self.source.get_slice(start, self.source.get_current_pos())
+        self.source.get_slice(start, self.source.get_current_pos())
     }
 
+    /// Space, tab, vertical tab and form feed
     fn is_whitespace(&self, byte: u8) -> bool {
-        byte == 32 || byte == 9
+        byte == 32 || byte == 9 || byte == 0x0B || byte == 0x0C
     }
 }
 
 // Numbers
-impl Lexer {
-    pub(super) fn number_handler<'a>(&mut self) -> &'a str {
+impl<'a> Lexer<'a> {
+    /// Consumes a number literal and returns whether it is an integer or a float
+    ///
+    /// * Integers are decimal, or hex/octal/binary with a `0x`/`0o`/`0b` prefix
+    /// * Floats are decimal, with a fraction (`1.5`), an exponent (`1e-3`) or a float suffix
+    /// * Digits can be separated with `_`, like `1_000_000`
+    /// * An optional type suffix follows, like `10u8` or `3.0f32`
+    ///
+    /// A `.` only starts a fraction if a digit follows it, so `1..2` and `1.foo()` keep their dots.
+    pub(super) fn number_handler(&mut self) -> TokenKind {
         let start = self.source.current_pos();
-        let mut has_had_dot = false;
+        let mut is_float = false;
+
+        let radix = match (self.read_byte(), self.peek_byte()) {
+            (b'0', Some(b'x' | b'X')) => 16,
+            (b'0', Some(b'o' | b'O')) => 8,
+            (b'0', Some(b'b' | b'B')) => 2,
+            _ => 10,
+        };
+
+        let has_digits = if radix == 10 {
+            self.digits_handler(10);
+
+            if self.at_byte(b'.') && self.peek_byte().is_some_and(|b| b.is_ascii_digit()) {
+                self.bump(); // move past the dot
+                self.digits_handler(10);
+                is_float = true;
+            }
+
+            if self.at_byte(b'e') || self.at_byte(b'E') {
+                let after_sign = match self.peek_byte() {
+                    Some(b'+' | b'-') => self.source.peek_nth(2),
+                    other => other,
+                };
+
+                // Without any digits, the `e` is left for the suffix check below
+                if after_sign.is_some_and(|b| b.is_ascii_digit()) {
+                    self.bump(); // move past the `e`
+                    if self.at_byte(b'+') || self.at_byte(b'-') {
+                        self.bump();
+                    }
+                    self.digits_handler(10);
+                    is_float = true;
+                }
+            }
+
+            true
+        } else {
+            // move past the prefix
+            self.bump();
+            self.bump();
+            self.digits_handler(radix)
+        };
+
+        let suffix_start = self.source.current_pos();
+        while !self.is_at_end()
+            && (self.read_byte().is_ascii_alphanumeric() || self.read_byte() == b'_')
+        {
+            self.bump();
+        }
+        let suffix = self
+            .source
+            .get_slice(suffix_start, self.source.get_current_pos());
+
+        if !has_digits || suffix == "e" || suffix == "E" {
+            // `0x`, `0b_`, `1e`, ...
+            self.error_from(LexErrorKind::MalformedNumber, start);
+        } else if !suffix.is_empty() {
+            let valid = match NumberSuffix::from_suffix(suffix) {
+                Some(suffix) if suffix.is_float() => {
+                    is_float = true;
+                    radix == 10
+                }
+                Some(_) => !is_float,
+                None => false,
+            };
+
+            if !valid {
+                self.error_from(LexErrorKind::InvalidNumberSuffix, suffix_start);
+            }
+        }
+
+        if is_float {
+            TokenKind::ValueFloat
+        } else {
+            TokenKind::ValueInteger
+        }
+    }
+
+    /// Consumes digits of the given radix and `_` separators, returning whether there was at
+    /// least one digit.
+    ///
+    /// Decimal digits that are too big for the radix (like the `2` in `0b102`) are consumed too,
+    /// but reported.
+    fn digits_handler(&mut self, radix: u32) -> bool {
+        let mut has_digits = false;
 
         while !self.is_at_end() {
             let byte = self.read_byte();
 
-            //if byte.is_ascii_digit() {
-            //    self.bump()
-            //} else if byte == b'.' && has_had_dot {
-            //    self.bump();
-            //    has_had_dot = true;
-            //}
-
-            if byte.is_ascii_digit() || (byte == b'.' && !has_had_dot) {
+            if byte == b'_' {
                 self.bump();
-                if byte == b'.' {
-                    has_had_dot = true;
-                }
+            } else if (byte as char).is_digit(radix) {
+                has_digits = true;
+                self.bump();
+            } else if byte.is_ascii_digit() {
+                let digit_start = self.source.current_pos();
+                has_digits = true;
+                self.bump();
+                self.error_from(LexErrorKind::InvalidDigit(radix), digit_start);
             } else {
                 break;
             }
         }
 
-        &self.source.get_slice(start, self.source.get_current_pos())
+        has_digits
+    }
+
+    fn at_byte(&self, byte: u8) -> bool {
+        !self.is_at_end() && self.read_byte() == byte
     }
 }
 
 // Strings
-impl Lexer {
-    pub(super) fn string_handler<'a>(&mut self) -> &'a str {
+impl<'a> Lexer<'a> {
+    pub(super) fn string_handler(&mut self) -> &'a str {
         let start = self.source.current_pos();
 
-        while !self.is_at_end() {
-            let byte = self.read_byte();
+        self.bump(); // move past the first quote
 
-            if byte == b'\\' {
-                self.bump(); // move past the backslash
-                             // TODO: Make an actual escape sequence handler - this is just a place holder
-                self.bump(); // move past the escaped character
+        loop {
+            if self.is_at_end() || is_line_break(self.read_byte()) {
+                self.error_from(LexErrorKind::UnterminatedString, start);
+                break;
             }
 
-            if byte != b'"' {
+            let byte = self.read_byte();
+
+            if byte == b'\\' {
+                self.escape_handler();
+            } else if byte == b'"' {
+                // move past the last quote
                 self.bump();
-            } else {
                 break;
+            } else {
+                self.bump_char();
             }
         }
 
-        // move past the last quote
-        self.bump();
-
-        &self.source.get_slice(start, self.source.get_current_pos())
+        self.source.get_slice(start, self.source.get_current_pos())
     }
 
-    pub(super) fn char_handler<'a>(&mut self) -> &'a str {
+    pub(super) fn char_handler(&mut self) -> &'a str {
         let start = self.source.current_pos();
 
         self.bump(); // move past the first quote
 
-        let next_byte = self.read_byte();
+        if !self.is_at_end() && self.read_byte() == b'\'' {
+            self.bump();
+            self.error_from(LexErrorKind::EmptyChar, start);
+            return self.source.get_slice(start, self.source.get_current_pos());
+        }
 
-        if next_byte == b'\\' {
-            self.bump(); // move past the backslash
+        if !self.is_at_end() && !is_line_break(self.read_byte()) {
+            if self.read_byte() == b'\\' {
+                self.escape_handler();
+            } else {
+                self.bump_char();
+            }
+        }
+
+        if !self.is_at_end() && self.read_byte() == b'\'' {
+            // move past the last quote
             self.bump();
         } else {
-            self.bump();
+            self.error_from(LexErrorKind::UnterminatedChar, start);
         }
 
-        // move past the last quote
-        self.bump();
+        self.source.get_slice(start, self.source.get_current_pos())
+    }
+
+    /// Moves past an escape sequence, the current byte being its backslash
+    fn escape_handler(&mut self) {
+        let start = self.source.current_pos();
+
+        self.bump(); // move past the backslash
+
+        let (value, len) = scan_escape(self.source.remaining());
+        self.source.advance(len);
+
+        if value.is_none() {
+            self.error_from(LexErrorKind::InvalidEscape, start);
+        }
+    }
+
+    /// Moves past the whole UTF-8 character at the current position
+    fn bump_char(&mut self) {
+        let len = utf8_len(self.read_byte()).unwrap_or(1);
+        self.source.advance(len);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn lex(input: &str) -> Vec<(TokenKind, &str)> {
+        let mut lexer = Lexer::new(input);
+        let mut tokens = vec![];
+
+        while !lexer.is_at_end() {
+            let tok = lexer.next_token();
+            tokens.push((tok.kind, &input[tok.span.start..tok.span.end]));
+        }
+
+        tokens
+    }
+
+    #[test]
+    fn iterator_ends_with_one_eof() {
+        let tokens = Lexer::new("a b").collect::<Vec<_>>();
+        let kinds = tokens.iter().map(|tok| tok.kind).collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Identifier,
+                TokenKind::Whitespace,
+                TokenKind::Identifier,
+                TokenKind::Eof,
+            ]
+        );
+        assert_eq!(tokens[3].span, Span { start: 3, end: 3 });
+
+        let tokens = Lexer::new("").collect::<Vec<_>>();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind, TokenKind::Eof);
+    }
+
+    /// Concatenating the text of every token gives back the input, byte for byte
+    fn assert_round_trips(input: &str) {
+        let mut rebuilt = String::new();
+        let mut end = 0;
+
+        for tok in Lexer::new(input) {
+            assert_eq!(tok.span.start, end, "gap or overlap before {:?}", tok);
+            rebuilt.push_str(&input[tok.span.start..tok.span.end]);
+            end = tok.span.end;
+        }
+
+        assert_eq!(rebuilt, input);
+    }
+
+    #[test]
+    fn round_trip() {
+        for input in [
+            "",
+            "func main() {\n    return 0x1F + 1.5e3;\n}\n",
+            "/* unterminated /* nested",
+            "\"unterminated\r\n'x",
+            "'\\u{1F600}' \"\\q\" ''",
+            "a\x00b\x01\x7F€😀変数",
+            "1..2 0x 1e 12abc 0b102",
+            "\r\r\n\n\x0B\x0C",
+        ] {
+            assert_round_trips(input);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip_any_input(input in "\\PC*") {
+            assert_round_trips(&input);
+        }
+
+        #[test]
+        fn round_trip_code_like_input(
+            input in r#"([a-z0-9_ \t\r\n'"\\/*.{}()<>=!&|^%+-]|u\{|0x|é|😀){0,64}"#
+        ) {
+            assert_round_trips(&input);
+        }
+    }
+
+    #[test]
+    fn operators_look_at_the_next_byte() {
+        assert_eq!(
+            lex(":é"),
+            vec![(TokenKind::SymColon, ":"), (TokenKind::Identifier, "é")]
+        );
+        assert_eq!(
+            lex("a::b!=c&&d"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::SymColcol, "::"),
+                (TokenKind::Identifier, "b"),
+                (TokenKind::OpNoteq, "!="),
+                (TokenKind::Identifier, "c"),
+                (TokenKind::OpAnd, "&&"),
+                (TokenKind::Identifier, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_eq!(
+            lex("a\r\nb\rc\n"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::Newline, "\r\n"),
+                (TokenKind::Identifier, "b"),
+                (TokenKind::Newline, "\r"),
+                (TokenKind::Identifier, "c"),
+                (TokenKind::Newline, "\n"),
+            ]
+        );
+        assert_eq!(
+            lex("\r\r\n"),
+            vec![(TokenKind::Newline, "\r"), (TokenKind::Newline, "\r\n")]
+        );
+        assert!(errors("a\r\nb\rc\n").is_empty());
+    }
+
+    #[test]
+    fn crlf_ends_comments_and_strings() {
+        assert_eq!(
+            lex("// hi\r\n\"ab\r\n"),
+            vec![
+                (TokenKind::LineComment, "// hi"),
+                (TokenKind::Newline, "\r\n"),
+                (TokenKind::ValueString, "\"ab"),
+                (TokenKind::Newline, "\r\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn form_feed_and_vertical_tab() {
+        assert_eq!(
+            lex("a \x0B\x0C\tb"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::Whitespace, " \x0B\x0C\t"),
+                (TokenKind::Identifier, "b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_line_endings() {
+        let input = "a\r\nb\nc\r\nd\r";
+        let mut lexer = Lexer::with_options(
+            input,
+            LexerOptions {
+                report_mixed_line_endings: true,
+                ..LexerOptions::default()
+            },
+        );
+
+        while !lexer.is_at_end() {
+            lexer.next_token();
+        }
+
+        let found = lexer
+            .errors()
+            .iter()
+            .map(|err| (err.kind, &input[err.span.start..err.span.end]))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            found,
+            vec![
+                (
+                    LexErrorKind::MixedLineEndings {
+                        expected: LineEnding::CrLf,
+                        found: LineEnding::Lf
+                    },
+                    "\n"
+                ),
+                (
+                    LexErrorKind::MixedLineEndings {
+                        expected: LineEnding::CrLf,
+                        found: LineEnding::Cr
+                    },
+                    "\r"
+                ),
+            ]
+        );
+
+        // Off by default
+        assert!(errors(input).is_empty());
+    }
+
+    #[test]
+    fn line_comment() {
+        assert_eq!(
+            lex("a // hi /* */\nb"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::LineComment, "// hi /* */"),
+                (TokenKind::Newline, "\n"),
+                (TokenKind::Identifier, "b"),
+            ]
+        );
+        assert_eq!(lex("//"), vec![(TokenKind::LineComment, "//")]);
+    }
+
+    #[test]
+    fn block_comment_nests() {
+        assert_eq!(
+            lex("/* a /* b */ c */x"),
+            vec![
+                (TokenKind::BlockComment, "/* a /* b */ c */"),
+                (TokenKind::Identifier, "x"),
+            ]
+        );
+        assert_eq!(lex("/**/"), vec![(TokenKind::BlockComment, "/**/")]);
+    }
+
+    #[test]
+    fn unterminated_block_comment_runs_to_end() {
+        assert_eq!(
+            lex("/* a /* b */"),
+            vec![(TokenKind::BlockComment, "/* a /* b */")]
+        );
+    }
+
+    #[test]
+    fn doc_comments() {
+        assert_eq!(
+            lex("/// doc\n//// not doc"),
+            vec![
+                (TokenKind::DocLineComment, "/// doc"),
+                (TokenKind::Newline, "\n"),
+                (TokenKind::LineComment, "//// not doc"),
+            ]
+        );
+        assert_eq!(
+            lex("/** doc */"),
+            vec![(TokenKind::DocBlockComment, "/** doc */")]
+        );
+        assert_eq!(
+            lex("/*** x */"),
+            vec![(TokenKind::BlockComment, "/*** x */")]
+        );
+        assert_eq!(lex("/**/"), vec![(TokenKind::BlockComment, "/**/")]);
+    }
+
+    fn errors(input: &str) -> Vec<(LexErrorKind, &str)> {
+        let mut lexer = Lexer::new(input);
+
+        while !lexer.is_at_end() {
+            lexer.next_token();
+        }
+
+        lexer
+            .take_errors()
+            .into_iter()
+            .map(|err| (err.kind, &input[err.span.start..err.span.end]))
+            .collect()
+    }
+
+    #[test]
+    fn stray_byte_is_error_token() {
+        assert_eq!(
+            lex("a\x01b"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::Error, "\x01"),
+                (TokenKind::Identifier, "b"),
+            ]
+        );
+        assert_eq!(errors("a\x01b"), vec![(LexErrorKind::StrayByte(1), "\x01")]);
+        assert_eq!(errors("\0"), vec![(LexErrorKind::StrayByte(0), "\0")]);
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(lex("\"abc\""), vec![(TokenKind::ValueString, "\"abc\"")]);
+        assert_eq!(
+            lex("\"a\\\"b\""),
+            vec![(TokenKind::ValueString, "\"a\\\"b\"")]
+        );
+        assert!(errors("\"abc\"").is_empty());
+    }
+
+    #[test]
+    fn unterminated_string() {
+        assert_eq!(
+            lex("\"abc\nx"),
+            vec![
+                (TokenKind::ValueString, "\"abc"),
+                (TokenKind::Newline, "\n"),
+                (TokenKind::Identifier, "x"),
+            ]
+        );
+        assert_eq!(
+            errors("\"abc"),
+            vec![(LexErrorKind::UnterminatedString, "\"abc")]
+        );
+    }
+
+    #[test]
+    fn chars() {
+        assert_eq!(lex("'a'"), vec![(TokenKind::ValueChar, "'a'")]);
+        assert_eq!(lex("'é'"), vec![(TokenKind::ValueChar, "'é'")]);
+        assert_eq!(errors("'a"), vec![(LexErrorKind::UnterminatedChar, "'a")]);
+        assert_eq!(errors("'"), vec![(LexErrorKind::UnterminatedChar, "'")]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            lex(r#""\n\t\\\"\'\0\x41\u{1F600}""#),
+            vec![(TokenKind::ValueString, r#""\n\t\\\"\'\0\x41\u{1F600}""#)]
+        );
+        assert!(errors(r#""\n\t\\\"\'\0\x41\u{1F600}""#).is_empty());
+        assert_eq!(lex(r"'\''"), vec![(TokenKind::ValueChar, r"'\''")]);
+        assert_eq!(lex(r"'\u{e9}'"), vec![(TokenKind::ValueChar, r"'\u{e9}'")]);
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        assert_eq!(
+            errors(r#""a\qb\xZZ\u{110000}""#),
+            vec![
+                (LexErrorKind::InvalidEscape, r"\q"),
+                (LexErrorKind::InvalidEscape, r"\x"),
+                (LexErrorKind::InvalidEscape, r"\u{110000}"),
+            ]
+        );
+        assert_eq!(errors(r"'\z'"), vec![(LexErrorKind::InvalidEscape, r"\z")]);
+        assert_eq!(errors("''"), vec![(LexErrorKind::EmptyChar, "''")]);
+    }
+
+    #[test]
+    fn unicode_identifiers() {
+        assert_eq!(
+            lex("héllo 変数 Ωmega"),
+            vec![
+                (TokenKind::Identifier, "héllo"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "変数"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "Ωmega"),
+            ]
+        );
+        // `e` followed by a combining acute accent
+        assert_eq!(
+            lex("cafe\u{301}"),
+            vec![(TokenKind::Identifier, "cafe\u{301}")]
+        );
+        assert!(errors("héllo 変数").is_empty());
+    }
+
+    #[test]
+    fn disallowed_unicode_characters() {
+        assert_eq!(
+            lex("a€b"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::Error, "€"),
+                (TokenKind::Identifier, "b"),
+            ]
+        );
+        assert_eq!(
+            errors("😀"),
+            vec![(LexErrorKind::UnexpectedChar('😀'), "😀")]
+        );
+        // A combining mark can continue an identifier, but not start one
+        assert_eq!(
+            errors("\u{301}"),
+            vec![(LexErrorKind::UnexpectedChar('\u{301}'), "\u{301}")]
+        );
+    }
+
+    #[test]
+    fn integers() {
+        for input in [
+            "0",
+            "42",
+            "1_000_000",
+            "0xFF",
+            "0Xdead_BEEF",
+            "0o17",
+            "0b1010",
+            "0b_1",
+            "10u8",
+            "0xFFi64",
+            "7usize",
+        ] {
+            assert_eq!(lex(input), vec![(TokenKind::ValueInteger, input)]);
+            assert!(errors(input).is_empty(), "{input}");
+        }
+    }
+
+    #[test]
+    fn floats() {
+        for input in [
+            "1.5",
+            "0.25",
+            "1e10",
+            "1.5e-3",
+            "2E+8",
+            "1_000.000_1",
+            "3.0f32",
+            "3f64",
+        ] {
+            assert_eq!(lex(input), vec![(TokenKind::ValueFloat, input)]);
+            assert!(errors(input).is_empty(), "{input}");
+        }
+    }
+
+    #[test]
+    fn dots_after_integers() {
+        assert_eq!(
+            lex("1..2"),
+            vec![
+                (TokenKind::ValueInteger, "1"),
+                (TokenKind::SymDot, "."),
+                (TokenKind::SymDot, "."),
+                (TokenKind::ValueInteger, "2"),
+            ]
+        );
+        assert_eq!(
+            lex("1.max"),
+            vec![
+                (TokenKind::ValueInteger, "1"),
+                (TokenKind::SymDot, "."),
+                (TokenKind::Identifier, "max"),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_number() {
+        assert_eq!(lex("0x"), vec![(TokenKind::ValueInteger, "0x")]);
+        assert_eq!(errors("0x"), vec![(LexErrorKind::MalformedNumber, "0x")]);
+        assert_eq!(errors("0b_"), vec![(LexErrorKind::MalformedNumber, "0b_")]);
+        assert_eq!(lex("1e"), vec![(TokenKind::ValueInteger, "1e")]);
+        assert_eq!(errors("1e"), vec![(LexErrorKind::MalformedNumber, "1e")]);
+        assert_eq!(
+            errors("1.5e"),
+            vec![(LexErrorKind::MalformedNumber, "1.5e")]
+        );
+        assert_eq!(errors("0b102"), vec![(LexErrorKind::InvalidDigit(2), "2")]);
+        assert_eq!(errors("0o78"), vec![(LexErrorKind::InvalidDigit(8), "8")]);
+    }
+
+    #[test]
+    fn invalid_number_suffix() {
+        assert_eq!(lex("12abc"), vec![(TokenKind::ValueInteger, "12abc")]);
+        assert_eq!(
+            errors("12abc"),
+            vec![(LexErrorKind::InvalidNumberSuffix, "abc")]
+        );
+        assert_eq!(
+            errors("1.5u8"),
+            vec![(LexErrorKind::InvalidNumberSuffix, "u8")]
+        );
+        assert_eq!(
+            errors("0x1p3"),
+            vec![(LexErrorKind::InvalidNumberSuffix, "p3")]
+        );
+        assert_eq!(
+            errors("0b1f32"),
+            vec![(LexErrorKind::InvalidNumberSuffix, "f32")]
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_reported() {
+        assert_eq!(
+            errors("/* a"),
+            vec![(LexErrorKind::UnterminatedBlockComment, "/* a")]
+        );
+    }
+
+    #[test]
+    fn slash_is_still_division() {
+        assert_eq!(
+            lex("a/b"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::OpDiv, "/"),
+                (TokenKind::Identifier, "b"),
+            ]
+        );
+        assert_eq!(lex("/="), vec![(TokenKind::OpDiveq, "/=")]);
+    }
+
+    #[test]
+    fn square_brackets_and_caret() {
+        assert_eq!(
+            lex("a[0]^b"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::BracketLsquared, "["),
+                (TokenKind::ValueInteger, "0"),
+                (TokenKind::BracketRsquared, "]"),
+                (TokenKind::OpBitXor, "^"),
+                (TokenKind::Identifier, "b"),
+            ]
+        );
+        assert_eq!(errors("\\"), vec![(LexErrorKind::StrayByte(b'\\'), "\\")]);
+    }
+
+    /// Every operator and punctuation token, with the text it is lexed from
+    const OPERATORS: &[(&str, TokenKind)] = &[
+        ("!", TokenKind::OpNot),
+        ("#", TokenKind::SymHash),
+        ("%", TokenKind::OpMod),
+        ("&", TokenKind::OpBitAnd),
+        ("(", TokenKind::BracketLparent),
+        (")", TokenKind::BracketRparent),
+        ("*", TokenKind::OpMul),
+        ("+", TokenKind::OpPlus),
+        (",", TokenKind::SymComma),
+        ("-", TokenKind::OpMinus),
+        (".", TokenKind::SymDot),
+        ("/", TokenKind::OpDiv),
+        (":", TokenKind::SymColon),
+        (";", TokenKind::SymSemiColon),
+        ("<", TokenKind::OpLt),
+        ("=", TokenKind::OpEq),
+        (">", TokenKind::OpGt),
+        ("?", TokenKind::SymQuestion),
+        ("@", TokenKind::SymAt),
+        ("[", TokenKind::BracketLsquared),
+        ("]", TokenKind::BracketRsquared),
+        ("^", TokenKind::OpBitXor),
+        ("{", TokenKind::BracketLcurly),
+        ("|", TokenKind::OpBitOr),
+        ("}", TokenKind::BracketRcurly),
+        ("~", TokenKind::OpBitNot),
+        ("!=", TokenKind::OpNoteq),
+        ("%=", TokenKind::OpModEq),
+        ("&&", TokenKind::OpAnd),
+        ("&=", TokenKind::OpBitAndEq),
+        ("*=", TokenKind::OpMuleq),
+        ("+=", TokenKind::OpPluseq),
+        ("-=", TokenKind::OpMinuseq),
+        ("->", TokenKind::OpArrow),
+        ("/=", TokenKind::OpDiveq),
+        ("::", TokenKind::SymColcol),
+        ("<<", TokenKind::OpBitLshift),
+        ("<=", TokenKind::OpLteq),
+        ("==", TokenKind::OpEqeq),
+        (">=", TokenKind::OpGteq),
+        (">>", TokenKind::OpBitRshift),
+        ("^=", TokenKind::OpBitXorEq),
+        ("|=", TokenKind::OpBitOrEq),
+        ("||", TokenKind::OpOr),
+        ("<<=", TokenKind::OpBitLshiftEq),
+        (">>=", TokenKind::OpBitRshiftEq),
+    ];
+
+    fn spans(input: &str) -> Vec<(TokenKind, Span)> {
+        Lexer::new(input)
+            .filter(|tok| tok.kind != TokenKind::Eof)
+            .map(|tok| (tok.kind, tok.span))
+            .collect()
+    }
+
+    #[test]
+    fn operators() {
+        for &(text, kind) in OPERATORS {
+            let len = text.len();
+            assert_eq!(
+                spans(text),
+                vec![(kind, Span { start: 0, end: len })],
+                "{:?}",
+                text
+            );
+
+            let input = format!("a{}b", text);
+            assert_eq!(
+                spans(&input),
+                vec![
+                    (TokenKind::Identifier, Span { start: 0, end: 1 }),
+                    (
+                        kind,
+                        Span {
+                            start: 1,
+                            end: 1 + len
+                        }
+                    ),
+                    (
+                        TokenKind::Identifier,
+                        Span {
+                            start: 1 + len,
+                            end: 2 + len
+                        }
+                    ),
+                ],
+                "{:?}",
+                input
+            );
+            assert_eq!(kind.text(), Some(text));
+        }
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(
+            lex("a << b <<= c<<<d"),
+            vec![
+                (TokenKind::Identifier, "a"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::OpBitLshift, "<<"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "b"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::OpBitLshiftEq, "<<="),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "c"),
+                (TokenKind::OpBitLshift, "<<"),
+                (TokenKind::OpLt, "<"),
+                (TokenKind::Identifier, "d"),
+            ]
+        );
+    }
+
+    /// Every run of up to three operator characters lexes as the longest operator it starts with
+    #[test]
+    fn operators_take_the_longest_match() {
+        let alphabet = OPERATORS
+            .iter()
+            .filter(|(text, _)| text.len() == 1)
+            .map(|(text, _)| text.as_bytes()[0])
+            .collect::<Vec<_>>();
+
+        let mut inputs = vec![vec![]];
+        for _ in 0..3 {
+            inputs = inputs
+                .iter()
+                .flat_map(|input| {
+                    alphabet.iter().map(move |&byte| {
+                        let mut input = input.clone();
+                        input.push(byte);
+                        input
+                    })
+                })
+                .collect();
+
+            for input in &inputs {
+                let input = std::str::from_utf8(input).unwrap();
+                if input.starts_with("//") || input.starts_with("/*") {
+                    continue;
+                }
+
+                let &(text, kind) = OPERATORS
+                    .iter()
+                    .filter(|(text, _)| input.starts_with(text))
+                    .max_by_key(|(text, _)| text.len())
+                    .unwrap();
+                assert_eq!(
+                    spans(input)[0],
+                    (
+                        kind,
+                        Span {
+                            start: 0,
+                            end: text.len()
+                        }
+                    ),
+                    "{:?}",
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn underscores() {
+        assert_eq!(
+            lex("_ _private __init a_b _0"),
+            vec![
+                (TokenKind::Underscore, "_"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "_private"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "__init"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "a_b"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "_0"),
+            ]
+        );
+        assert_eq!(errors("_private __init _"), vec![]);
+    }
+
+    #[test]
+    fn dollar_signs() {
+        assert_eq!(
+            lex("$x a$b $"),
+            vec![
+                (TokenKind::Identifier, "$x"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "a$b"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "$"),
+            ]
+        );
+    }
 
-        &self.source.get_slice(start, self.source.get_current_pos())
+    #[test]
+    fn raw_identifiers() {
+        assert_eq!(
+            lex("r#class r#return r#_x ré r#é"),
+            vec![
+                (TokenKind::Identifier, "r#class"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "r#return"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "r#_x"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "ré"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "r#é"),
+            ]
+        );
+
+        // Without an identifier right after the `#` it is just `r` and a `#`
+        assert_eq!(
+            lex("r #x r#1 r#"),
+            vec![
+                (TokenKind::Identifier, "r"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::SymHash, "#"),
+                (TokenKind::Identifier, "x"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "r"),
+                (TokenKind::SymHash, "#"),
+                (TokenKind::ValueInteger, "1"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Identifier, "r"),
+                (TokenKind::SymHash, "#"),
+            ]
+        );
     }
 }
@@ -0,0 +1,156 @@
+//! Relexing only the part of a text an edit changed
+//!
+//! Lexing a token only looks at the text from where it starts on, so once the lexer starts a
+//! token after the edit at a place where a token started before it, every token from there on
+//! comes out the same as before, only moved by the edit. [`relex`] lexes from a little before the
+//! edit up to that point, and keeps the old tokens on both sides of it.
+
+use super::{Lexer, LexerOptions};
+use crate::ast::{Span, Token, TokenKind};
+use crate::text_edit::TextEdit;
+
+/// The tokens of an edited text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relexed {
+    /// Every token of the new text, ending with [`TokenKind::Eof`] like the [`Lexer`] does
+    pub tokens: Vec<Token>,
+    /// The part of the new text that was lexed again, the tokens outside of it are the old ones
+    pub changed: Span,
+}
+
+/// Updates the tokens of a text for `edit`, lexing again only what the edit could have changed
+///
+/// `old_tokens` are all the tokens of the text before the edit, and `new_text` is the text after
+/// it. `options` have to be the ones the old tokens were lexed with. Unlike a [`Lexer`] this does
+/// not report errors, they have to come from lexing the changed part on its own.
+pub fn relex(
+    old_tokens: &[Token],
+    edit: &TextEdit,
+    new_text: &str,
+    options: LexerOptions,
+) -> Relexed {
+    // The token before the one the edit starts in or touches could have looked ahead into it,
+    // like the `r` of `r#x` does
+    let first = old_tokens
+        .partition_point(|token| token.span.end < edit.range.start)
+        .saturating_sub(1);
+    let start = old_tokens.get(first).map_or(0, |token| token.span.start);
+
+    let mut tokens = old_tokens[..first].to_vec();
+
+    for mut token in Lexer::with_options(&new_text[start..], options) {
+        token.span.start += start;
+        token.span.end += start;
+
+        if token.span.start >= edit.new_end() {
+            let old_start = token.span.start - edit.new_end() + edit.range.end;
+            if let Ok(index) = old_tokens.binary_search_by_key(&old_start, |old| old.span.start) {
+                tokens.extend(old_tokens[index..].iter().map(|old| Token {
+                    kind: old.kind,
+                    span: Span {
+                        start: edit.shift(old.span.start),
+                        end: edit.shift(old.span.end),
+                    },
+                }));
+                return Relexed {
+                    tokens,
+                    changed: Span {
+                        start,
+                        end: token.span.start,
+                    },
+                };
+            }
+        }
+
+        tokens.push(token);
+        if token.kind == TokenKind::Eof {
+            break;
+        }
+    }
+
+    Relexed {
+        tokens,
+        changed: Span {
+            start,
+            end: new_text.len(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn check(old_text: &str, range: std::ops::Range<usize>, new_text: &str) -> Relexed {
+        let edit = TextEdit::new(
+            Span {
+                start: range.start,
+                end: range.end,
+            },
+            new_text,
+        );
+        let edited = edit.apply(old_text);
+
+        let old_tokens = Lexer::new(old_text).collect::<Vec<_>>();
+        let relexed = relex(&old_tokens, &edit, &edited, LexerOptions::default());
+        assert_eq!(
+            relexed.tokens,
+            Lexer::new(&edited).collect::<Vec<_>>(),
+            "{:?} -> {:?}",
+            old_text,
+            edited
+        );
+        relexed
+    }
+
+    #[test]
+    fn only_the_edited_token_changes() {
+        let relexed = check("let abc = 1;\nlet d = 2;", 5..6, "xyz");
+        assert_eq!(relexed.changed, Span { start: 3, end: 9 });
+    }
+
+    #[test]
+    fn edits_that_join_and_split_tokens() {
+        check("a b", 1..2, "");
+        check("ab", 1..1, " ");
+        check("r #x", 1..2, "");
+        check("r#", 2..2, "x");
+        check("1..2", 2..3, "5");
+        check("a > b", 3..3, ">");
+    }
+
+    #[test]
+    fn edits_that_change_the_rest_of_the_text() {
+        let relexed = check("a /* b */ c", 7..9, "");
+        assert_eq!(relexed.changed.end, 9);
+        check("x = \"a\"; y = 1;", 4..5, "");
+        check("", 0..0, "func");
+        check("func", 0..4, "");
+    }
+
+    /// A byte offset into `text` that is on a character boundary
+    fn boundary(text: &str, index: prop::sample::Index) -> usize {
+        let boundaries = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([text.len()])
+            .collect::<Vec<_>>();
+        *index.get(&boundaries)
+    }
+
+    proptest! {
+        #[test]
+        fn same_tokens_as_lexing_everything(
+            old_text in r#"([a-z0-9_ \t\r\n#'"\\/*.{}()<>=!&|^%+-]|r#|0x|é|😀){0,48}"#,
+            a in any::<prop::sample::Index>(),
+            b in any::<prop::sample::Index>(),
+            new_text in r#"([a-z0-9_ \n#'"\\/*.{}<>=]|r#|é){0,8}"#,
+        ) {
+            let (a, b) = (boundary(&old_text, a), boundary(&old_text, b));
+            let relexed = check(&old_text, a.min(b)..a.max(b), &new_text);
+            prop_assert!(relexed.changed.start <= a.min(b));
+        }
+    }
+}
@@ -0,0 +1,13 @@
+pub mod ast;
+pub mod error;
+pub mod escape;
+pub mod hir;
+pub mod keywords;
+pub mod lexer;
+pub mod line_index;
+pub mod number;
+pub mod parser;
+mod source;
+pub mod syntax_kind;
+pub mod syntax_node;
+pub mod text_edit;
@@ -0,0 +1,274 @@
+//! Converting between byte offsets (what a [`crate::ast::Span`] holds) and line/column positions
+//!
+//! Editors don't agree on what a column is: LSP clients count UTF-16 code units by default, some
+//! count code points, and everything in here counts bytes. [`LineIndex`] converts between all of
+//! them.
+
+use std::collections::HashMap;
+
+/// What a column counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Bytes
+    Utf8,
+    /// UTF-16 code units, the LSP default
+    Utf16,
+    /// Unicode code points
+    Utf32,
+}
+
+impl Encoding {
+    fn len(self, c: char) -> usize {
+        match self {
+            Encoding::Utf8 => c.len_utf8(),
+            Encoding::Utf16 => c.len_utf16(),
+            Encoding::Utf32 => 1,
+        }
+    }
+}
+
+/// A zero-based line and column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A non-ASCII character, and its byte offset relative to the start of its line
+#[derive(Debug, Clone, Copy)]
+struct WideChar {
+    start: usize,
+    c: char,
+}
+
+/// Index of where every line starts, built once per source text
+///
+/// Lines end at `\n`, `\r\n` or a lone `\r`. Looking up a line is a binary search over the line
+/// starts, so converting either way is `O(log n)` in the number of lines (plus the number of
+/// non-ASCII characters on that line, for UTF-16 and UTF-32 columns).
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    /// Byte offset where each line starts, the first line always starts at 0
+    line_starts: Vec<usize>,
+    /// Byte offset where each line's content ends, before its line break
+    line_ends: Vec<usize>,
+    /// Non-ASCII characters, only for lines that have any
+    wide_chars: HashMap<u32, Vec<WideChar>>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> LineIndex {
+        let mut line_starts = vec![0];
+        let mut line_ends = vec![];
+        let mut wide_chars: HashMap<u32, Vec<WideChar>> = HashMap::new();
+
+        let bytes = text.as_bytes();
+        let mut chars = text.char_indices();
+
+        while let Some((offset, c)) = chars.next() {
+            let line_start = *line_starts.last().unwrap();
+
+            match c {
+                '\n' | '\r' => {
+                    line_ends.push(offset);
+
+                    let next = if c == '\r' && bytes.get(offset + 1) == Some(&b'\n') {
+                        chars.next();
+                        offset + 2
+                    } else {
+                        offset + 1
+                    };
+                    line_starts.push(next);
+                }
+                c if !c.is_ascii() => {
+                    let line = (line_starts.len() - 1) as u32;
+                    wide_chars.entry(line).or_default().push(WideChar {
+                        start: offset - line_start,
+                        c,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        line_ends.push(text.len());
+
+        LineIndex {
+            line_starts,
+            line_ends,
+            wide_chars,
+        }
+    }
+
+    /// How many lines there are, a trailing line break starts one last empty line
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Converts a byte offset to a position, with the column counted in `encoding`
+    ///
+    /// Offsets past the end of the text are clamped to the end, offsets inside of a line break
+    /// or a multi-byte character are moved back to its start.
+    pub fn position(&self, offset: usize, encoding: Encoding) -> Position {
+        let offset = offset.min(*self.line_ends.last().unwrap());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+
+        // Inside of the line break itself
+        let offset = offset.min(self.line_ends[line]);
+        let byte_column = offset - self.line_starts[line];
+        let mut column = byte_column;
+
+        for wide in self.wide_chars(line) {
+            if wide.start >= byte_column {
+                break;
+            }
+
+            let utf8_len = wide.c.len_utf8();
+            if wide.start + utf8_len > byte_column {
+                // In the middle of the character
+                column -= byte_column - wide.start;
+                break;
+            }
+            column -= utf8_len - encoding.len(wide.c);
+        }
+
+        Position {
+            line: line as u32,
+            column: column as u32,
+        }
+    }
+
+    /// Converts a position with its column counted in `encoding` back to a byte offset
+    ///
+    /// Returns [`None`] if the line does not exist. A column past the end of its line is clamped to
+    /// the end of the line, like LSP asks for, and a column in the middle of a character is moved
+    /// back to its start.
+    pub fn offset(&self, position: Position, encoding: Encoding) -> Option<usize> {
+        let line = position.line as usize;
+        let line_start = *self.line_starts.get(line)?;
+        let line_len = self.line_ends[line] - line_start;
+
+        let mut remaining = position.column as usize;
+        let mut column = 0;
+
+        for wide in self.wide_chars(line) {
+            let ascii_before = wide.start - column;
+            if remaining <= ascii_before {
+                break;
+            }
+
+            remaining -= ascii_before;
+            column = wide.start;
+
+            let wide_len = encoding.len(wide.c);
+            if remaining < wide_len {
+                // In the middle of the character
+                return Some(line_start + column);
+            }
+
+            remaining -= wide_len;
+            column += wide.c.len_utf8();
+        }
+
+        Some(line_start + (column + remaining).min(line_len))
+    }
+
+    fn wide_chars(&self, line: usize) -> &[WideChar] {
+        self.wide_chars
+            .get(&(line as u32))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pos(line: u32, column: u32) -> Position {
+        Position { line, column }
+    }
+
+    #[test]
+    fn line_endings() {
+        let index = LineIndex::new("a\nb\r\nc\rd");
+        assert_eq!(index.line_count(), 4);
+
+        assert_eq!(index.position(0, Encoding::Utf8), pos(0, 0));
+        assert_eq!(index.position(2, Encoding::Utf8), pos(1, 0));
+        assert_eq!(index.position(5, Encoding::Utf8), pos(2, 0));
+        assert_eq!(index.position(7, Encoding::Utf8), pos(3, 0));
+        assert_eq!(index.position(8, Encoding::Utf8), pos(3, 1));
+
+        // Inside of `\r\n`
+        assert_eq!(index.position(4, Encoding::Utf8), pos(1, 1));
+
+        assert_eq!(index.offset(pos(1, 0), Encoding::Utf8), Some(2));
+        assert_eq!(index.offset(pos(2, 0), Encoding::Utf8), Some(5));
+        assert_eq!(index.offset(pos(3, 1), Encoding::Utf8), Some(8));
+        assert_eq!(index.offset(pos(4, 0), Encoding::Utf8), None);
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let index = LineIndex::new("a\n");
+        assert_eq!(index.line_count(), 2);
+        assert_eq!(index.position(2, Encoding::Utf8), pos(1, 0));
+        assert_eq!(index.position(100, Encoding::Utf8), pos(1, 0));
+    }
+
+    #[test]
+    fn wide_columns() {
+        // `é` is 2 bytes and 1 UTF-16 unit, `😀` is 4 bytes and 2 UTF-16 units
+        let text = "x = \"é😀\" + y";
+        let index = LineIndex::new(text);
+        let y = text.find('y').unwrap();
+
+        assert_eq!(index.position(y, Encoding::Utf8), pos(0, 15));
+        assert_eq!(index.position(y, Encoding::Utf16), pos(0, 12));
+        assert_eq!(index.position(y, Encoding::Utf32), pos(0, 11));
+
+        assert_eq!(index.offset(pos(0, 15), Encoding::Utf8), Some(y));
+        assert_eq!(index.offset(pos(0, 12), Encoding::Utf16), Some(y));
+        assert_eq!(index.offset(pos(0, 11), Encoding::Utf32), Some(y));
+    }
+
+    #[test]
+    fn inside_of_a_character() {
+        let text = "😀a";
+        let index = LineIndex::new(text);
+
+        assert_eq!(index.position(2, Encoding::Utf8), pos(0, 0));
+        assert_eq!(index.position(2, Encoding::Utf16), pos(0, 0));
+        // Between the two halves of the surrogate pair
+        assert_eq!(index.offset(pos(0, 1), Encoding::Utf16), Some(0));
+        assert_eq!(index.offset(pos(0, 2), Encoding::Utf16), Some(4));
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "func main() {\r\n    let ü = \"日本\";\n\treturn ü;\r}\n";
+        let index = LineIndex::new(text);
+
+        for encoding in [Encoding::Utf8, Encoding::Utf16, Encoding::Utf32] {
+            for (offset, c) in text.char_indices() {
+                if c == '\n' && text[..offset].ends_with('\r') {
+                    continue;
+                }
+                let position = index.position(offset, encoding);
+                assert_eq!(
+                    index.offset(position, encoding),
+                    Some(offset),
+                    "{encoding:?} {offset}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn column_past_line_end_is_clamped() {
+        let index = LineIndex::new("ab\ncd");
+        assert_eq!(index.offset(pos(0, 10), Encoding::Utf16), Some(2));
+        assert_eq!(index.offset(pos(1, 10), Encoding::Utf8), Some(5));
+    }
+}
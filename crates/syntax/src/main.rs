@@ -1,21 +1,19 @@
-mod ast;
-mod lexer;
-mod source;
-
 use oxc_allocator::Allocator;
+use syntax::{hir, parser};
 
 const INPUT: &str = include_str!("../../../test.sn");
 
 fn main() {
-    let input = "\n";
     let allocator = Allocator::default();
 
-    let mut lexer = lexer::Lexer::new(INPUT);
+    let parse = parser::parse(INPUT);
+    let program = hir::lower(&allocator, &parse.tree());
+    println!("{:#?}", program);
 
-    while !lexer.is_at_end() {
-        let tok = lexer.next_token();
-        let span = tok.span;
-        let c = &INPUT[span.start..span.end];
-        println!("{:?}: `{}`", tok.kind, c);
+    for err in &parse.lex_errors {
+        println!("error: {}", err);
+    }
+    for err in &parse.errors {
+        println!("error: {}", err);
     }
 }
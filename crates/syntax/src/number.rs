@@ -0,0 +1,444 @@
+//! Evaluating number literals
+//!
+//! The lexer only checks that a number literal is well formed, [`evaluate`] works out the value it
+//! stands for. This is what constant folding, hovers (`0xFF = 255`) and overflow warnings use.
+
+use std::fmt;
+
+/// The type suffix of a number literal, like the `u8` in `10u8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    F32,
+    F64,
+}
+
+impl NumberSuffix {
+    /// Looks up a suffix by how it is written, returns [`None`] if it isn't a known type
+    pub fn from_suffix(suffix: &str) -> Option<NumberSuffix> {
+        Some(match suffix {
+            "i8" => NumberSuffix::I8,
+            "i16" => NumberSuffix::I16,
+            "i32" => NumberSuffix::I32,
+            "i64" => NumberSuffix::I64,
+            "i128" => NumberSuffix::I128,
+            "isize" => NumberSuffix::Isize,
+            "u8" => NumberSuffix::U8,
+            "u16" => NumberSuffix::U16,
+            "u32" => NumberSuffix::U32,
+            "u64" => NumberSuffix::U64,
+            "u128" => NumberSuffix::U128,
+            "usize" => NumberSuffix::Usize,
+            "f32" => NumberSuffix::F32,
+            "f64" => NumberSuffix::F64,
+            _ => return None,
+        })
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NumberSuffix::I8 => "i8",
+            NumberSuffix::I16 => "i16",
+            NumberSuffix::I32 => "i32",
+            NumberSuffix::I64 => "i64",
+            NumberSuffix::I128 => "i128",
+            NumberSuffix::Isize => "isize",
+            NumberSuffix::U8 => "u8",
+            NumberSuffix::U16 => "u16",
+            NumberSuffix::U32 => "u32",
+            NumberSuffix::U64 => "u64",
+            NumberSuffix::U128 => "u128",
+            NumberSuffix::Usize => "usize",
+            NumberSuffix::F32 => "f32",
+            NumberSuffix::F64 => "f64",
+        }
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, NumberSuffix::F32 | NumberSuffix::F64)
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            NumberSuffix::I8
+                | NumberSuffix::I16
+                | NumberSuffix::I32
+                | NumberSuffix::I64
+                | NumberSuffix::I128
+                | NumberSuffix::Isize
+        )
+    }
+
+    /// The biggest value a literal of an integer type can have, `isize`/`usize` are taken to be
+    /// 64 bits wide
+    fn int_max(self) -> u128 {
+        match self {
+            NumberSuffix::I8 => i8::MAX as u128,
+            NumberSuffix::I16 => i16::MAX as u128,
+            NumberSuffix::I32 => i32::MAX as u128,
+            NumberSuffix::I64 | NumberSuffix::Isize => i64::MAX as u128,
+            NumberSuffix::I128 => i128::MAX as u128,
+            NumberSuffix::U8 => u8::MAX as u128,
+            NumberSuffix::U16 => u16::MAX as u128,
+            NumberSuffix::U32 => u32::MAX as u128,
+            NumberSuffix::U64 | NumberSuffix::Usize => u64::MAX as u128,
+            NumberSuffix::U128 => u128::MAX,
+            NumberSuffix::F32 | NumberSuffix::F64 => unreachable!("float suffixes have no int max"),
+        }
+    }
+}
+
+impl fmt::Display for NumberSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The value of a number literal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberValue {
+    /// Integers that fit in an `i64` (and are not typed as unsigned)
+    Signed(i64),
+    /// Integers that don't fit in an `i64`, or are typed as unsigned, but fit in a `u64`
+    Unsigned(u64),
+    /// Integers that need more than 64 bits
+    Big(u128),
+    Float(f64),
+}
+
+impl fmt::Display for NumberValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberValue::Signed(value) => write!(f, "{}", value),
+            NumberValue::Unsigned(value) => write!(f, "{}", value),
+            NumberValue::Big(value) => write!(f, "{}", value),
+            NumberValue::Float(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+/// Problems with the value of a number literal, the literal itself is well formed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberDiagnostic {
+    /// The value does not fit in its type, or in 128 bits if it has no type
+    Overflow { ty: Option<NumberSuffix> },
+    /// The value can't be represented exactly by its float type
+    PrecisionLoss { ty: NumberSuffix },
+}
+
+impl fmt::Display for NumberDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberDiagnostic::Overflow { ty: Some(ty) } => {
+                write!(f, "literal out of range for `{}`", ty)
+            }
+            NumberDiagnostic::Overflow { ty: None } => {
+                write!(f, "literal out of range for any integer type")
+            }
+            NumberDiagnostic::PrecisionLoss { ty } => {
+                write!(f, "literal can't be represented exactly as `{}`", ty)
+            }
+        }
+    }
+}
+
+/// An evaluated number literal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberLiteral {
+    /// The value, saturated to the biggest value of its type if it overflowed
+    pub value: NumberValue,
+    pub suffix: Option<NumberSuffix>,
+    pub diagnostic: Option<NumberDiagnostic>,
+}
+
+/// Evaluates the text of a [`crate::ast::TokenKind::ValueInteger`] or
+/// [`crate::ast::TokenKind::ValueFloat`] token.
+///
+/// Returns [`None`] for malformed literals (no digits, or an unknown suffix), the lexer has
+/// already reported those.
+pub fn evaluate(text: &str) -> Option<NumberLiteral> {
+    let bytes = text.as_bytes();
+
+    let (radix, digits_start) = match bytes {
+        [b'0', b'x' | b'X', ..] => (16, 2),
+        [b'0', b'o' | b'O', ..] => (8, 2),
+        [b'0', b'b' | b'B', ..] => (2, 2),
+        _ => (10, 0),
+    };
+
+    let mut digits_end = digits_start;
+    let mut is_float = false;
+
+    if radix == 10 {
+        digits_end = skip_digits(bytes, digits_end, 10);
+
+        if bytes.get(digits_end) == Some(&b'.') {
+            digits_end = skip_digits(bytes, digits_end + 1, 10);
+            is_float = true;
+        }
+
+        if matches!(bytes.get(digits_end), Some(b'e' | b'E')) {
+            let mut exp = digits_end + 1;
+            if matches!(bytes.get(exp), Some(b'+' | b'-')) {
+                exp += 1;
+            }
+
+            if bytes.get(exp).is_some_and(|b| b.is_ascii_digit()) {
+                digits_end = skip_digits(bytes, exp, 10);
+                is_float = true;
+            }
+        }
+    } else {
+        digits_end = skip_digits(bytes, digits_end, radix);
+    }
+
+    let digits = text[digits_start..digits_end].replace('_', "");
+    let suffix = match &text[digits_end..] {
+        "" => None,
+        suffix => Some(NumberSuffix::from_suffix(suffix)?),
+    };
+
+    if digits.is_empty() {
+        return None;
+    }
+
+    if is_float || suffix.is_some_and(NumberSuffix::is_float) {
+        // `1.5u8`
+        if suffix.is_some_and(|suffix| !suffix.is_float()) {
+            return None;
+        }
+        evaluate_float(&digits, radix, suffix)
+    } else {
+        Some(evaluate_int(&digits, radix, suffix))
+    }
+}
+
+fn skip_digits(bytes: &[u8], mut index: usize, radix: u32) -> usize {
+    while bytes
+        .get(index)
+        .is_some_and(|b| *b == b'_' || (*b as char).is_digit(radix))
+    {
+        index += 1;
+    }
+
+    index
+}
+
+fn evaluate_int(digits: &str, radix: u32, suffix: Option<NumberSuffix>) -> NumberLiteral {
+    let (value, mut diagnostic) = match u128::from_str_radix(digits, radix) {
+        Ok(value) => (value, None),
+        Err(_) => (u128::MAX, Some(NumberDiagnostic::Overflow { ty: suffix })),
+    };
+
+    let value = match suffix {
+        Some(ty) => {
+            let max = ty.int_max();
+            if value > max {
+                diagnostic = Some(NumberDiagnostic::Overflow { ty: Some(ty) });
+            }
+            let value = value.min(max);
+
+            match ty {
+                NumberSuffix::I128 | NumberSuffix::U128 => NumberValue::Big(value),
+                _ if ty.is_signed() => NumberValue::Signed(value as i64),
+                _ => NumberValue::Unsigned(value as u64),
+            }
+        }
+        None => {
+            if let Ok(value) = i64::try_from(value) {
+                NumberValue::Signed(value)
+            } else if let Ok(value) = u64::try_from(value) {
+                NumberValue::Unsigned(value)
+            } else {
+                NumberValue::Big(value)
+            }
+        }
+    };
+
+    NumberLiteral {
+        value,
+        suffix,
+        diagnostic,
+    }
+}
+
+fn evaluate_float(digits: &str, radix: u32, suffix: Option<NumberSuffix>) -> Option<NumberLiteral> {
+    // `0x1f32` is an integer, hex floats don't exist
+    if radix != 10 {
+        return None;
+    }
+
+    let ty = suffix.unwrap_or(NumberSuffix::F64);
+    let mut diagnostic = None;
+
+    let value = if ty == NumberSuffix::F32 {
+        let value = digits.parse::<f32>().ok()?;
+        if value.is_infinite() {
+            diagnostic = Some(NumberDiagnostic::Overflow { ty: Some(ty) });
+        } else if loses_precision(digits, value == 0.0, &value.to_string()) {
+            diagnostic = Some(NumberDiagnostic::PrecisionLoss { ty });
+        }
+        value as f64
+    } else {
+        let value = digits.parse::<f64>().ok()?;
+        if value.is_infinite() {
+            diagnostic = Some(NumberDiagnostic::Overflow { ty: Some(ty) });
+        } else if loses_precision(digits, value == 0.0, &value.to_string()) {
+            diagnostic = Some(NumberDiagnostic::PrecisionLoss { ty });
+        }
+        value
+    };
+
+    Some(NumberLiteral {
+        value: NumberValue::Float(value),
+        suffix,
+        diagnostic,
+    })
+}
+
+/// Whether a float literal lost information that is worth telling about
+///
+/// Decimal fractions like `0.1` are never exact, so only two cases are reported:
+/// * A non-zero literal that rounded to zero
+/// * An integer literal (like `16777217f32`) that is not exactly representable
+fn loses_precision(digits: &str, is_zero: bool, shortest: &str) -> bool {
+    let literal_is_zero = digits
+        .bytes()
+        .take_while(|b| *b != b'e' && *b != b'E')
+        .all(|b| b == b'0' || b == b'.');
+
+    if is_zero {
+        return !literal_is_zero;
+    }
+
+    if digits.bytes().all(|b| b.is_ascii_digit()) {
+        // Shortest round-trip representation, so any difference means the value changed
+        return shortest != digits.trim_start_matches('0');
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn value(text: &str) -> NumberValue {
+        evaluate(text).unwrap().value
+    }
+
+    fn diagnostic(text: &str) -> Option<NumberDiagnostic> {
+        evaluate(text).unwrap().diagnostic
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(value("42"), NumberValue::Signed(42));
+        assert_eq!(value("0xFF"), NumberValue::Signed(255));
+        assert_eq!(value("0o17"), NumberValue::Signed(15));
+        assert_eq!(value("0b1010"), NumberValue::Signed(10));
+        assert_eq!(value("1_000_000"), NumberValue::Signed(1_000_000));
+        assert_eq!(value("10u8"), NumberValue::Unsigned(10));
+        assert_eq!(value("0xFFi64"), NumberValue::Signed(255));
+        assert_eq!(evaluate("10u8").unwrap().suffix, Some(NumberSuffix::U8));
+    }
+
+    #[test]
+    fn wide_integers() {
+        assert_eq!(
+            value("18446744073709551615"),
+            NumberValue::Unsigned(u64::MAX)
+        );
+        assert_eq!(
+            value("18446744073709551616"),
+            NumberValue::Big(u64::MAX as u128 + 1)
+        );
+        assert_eq!(value("1u128"), NumberValue::Big(1));
+    }
+
+    #[test]
+    fn integer_overflow() {
+        assert_eq!(
+            diagnostic("256u8"),
+            Some(NumberDiagnostic::Overflow {
+                ty: Some(NumberSuffix::U8)
+            })
+        );
+        assert_eq!(value("256u8"), NumberValue::Unsigned(255));
+        assert_eq!(diagnostic("255u8"), None);
+        assert_eq!(diagnostic("128i8"), diagnostic("256i8"));
+        assert_eq!(
+            diagnostic("0x1_0000_0000_0000_0000_0000_0000_0000_0000"),
+            Some(NumberDiagnostic::Overflow { ty: None })
+        );
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(value("1.5"), NumberValue::Float(1.5));
+        assert_eq!(value("1.5e-3"), NumberValue::Float(1.5e-3));
+        assert_eq!(value("2E+8"), NumberValue::Float(2e8));
+        assert_eq!(value("3f32"), NumberValue::Float(3.0));
+        assert_eq!(value("1_000.5"), NumberValue::Float(1000.5));
+        assert_eq!(diagnostic("0.1"), None);
+        assert_eq!(diagnostic("0.0"), None);
+    }
+
+    #[test]
+    fn float_overflow_and_precision() {
+        assert_eq!(
+            diagnostic("1e39f32"),
+            Some(NumberDiagnostic::Overflow {
+                ty: Some(NumberSuffix::F32)
+            })
+        );
+        assert_eq!(
+            diagnostic("1e-50f32"),
+            Some(NumberDiagnostic::PrecisionLoss {
+                ty: NumberSuffix::F32
+            })
+        );
+        assert_eq!(
+            diagnostic("16777217f32"),
+            Some(NumberDiagnostic::PrecisionLoss {
+                ty: NumberSuffix::F32
+            })
+        );
+        assert_eq!(diagnostic("16777216f32"), None);
+        assert_eq!(
+            diagnostic("9007199254740993f64"),
+            Some(NumberDiagnostic::PrecisionLoss {
+                ty: NumberSuffix::F64
+            })
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(evaluate("0x"), None);
+        assert_eq!(evaluate("12abc"), None);
+        assert_eq!(evaluate("1.5u8"), None);
+        assert_eq!(
+            evaluate("0x1f32").unwrap().value,
+            NumberValue::Signed(0x1f32)
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(value("0xFF").to_string(), "255");
+        assert_eq!(value("1e3").to_string(), "1000.0");
+    }
+}
@@ -0,0 +1,675 @@
+//! Recursive-descent parser, turning the tokens of the lexer into the concrete syntax tree
+//!
+//! The grammar lives in the submodules, one function per node, and only ever looks at
+//! significant tokens. [`Parser`] adds the whitespace and comments in between to the tree as it
+//! goes, so the tree stays lossless without the grammar having to think about trivia.
+
+mod decls;
+mod expressions;
+mod reparse;
+mod statements;
+mod types;
+
+use std::cell::Cell;
+
+use rowan::{Checkpoint, GreenNode};
+
+use crate::ast::{AstNode, SourceFile, Span, Token, TokenKind};
+use crate::error::{LexError, ParseError};
+use crate::lexer::{Lexer, LexerOptions};
+use crate::syntax_kind::SyntaxKind;
+use crate::syntax_node::{SyntaxNode, TreeBuilder};
+
+pub(crate) use reparse::reparse;
+
+/// The result of parsing a source text: its tree, and everything that went wrong on the way
+#[derive(Debug, Clone)]
+pub struct Parse {
+    green: GreenNode,
+    pub errors: Vec<ParseError>,
+    pub lex_errors: Vec<LexError>,
+    /// What the text was lexed with, to lex edits to it the same way
+    options: LexerOptions,
+}
+
+impl Parse {
+    pub fn green(&self) -> &GreenNode {
+        &self.green
+    }
+
+    /// The root [`SyntaxKind::SourceFile`] node
+    pub fn syntax_node(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    /// The root node, as a typed [`SourceFile`]
+    pub fn tree(&self) -> SourceFile {
+        SourceFile::cast(self.syntax_node()).unwrap()
+    }
+}
+
+/// Parses a whole source file
+pub fn parse(text: &str) -> Parse {
+    parse_with_options(text, LexerOptions::default())
+}
+
+/// Parses a whole source file, lexing it with `options`, like the edition it is written in
+pub fn parse_with_options(text: &str, options: LexerOptions) -> Parse {
+    let mut lexer = Lexer::with_options(text, options);
+    let tokens = (&mut lexer).collect::<Vec<_>>();
+
+    let mut p = Parser::new(text, tokens);
+    decls::source_file(&mut p);
+    Parse {
+        options,
+        ..p.finish(lexer.take_errors())
+    }
+}
+
+pub(crate) struct Parser<'a> {
+    builder: TreeBuilder<'a>,
+    /// Every token of the source, trivia included but without the final `Eof`
+    tokens: Vec<Token>,
+    /// Index of the first token not yet added to the tree
+    pos: usize,
+    /// Where the last significant token added to the tree ends
+    prev_end: usize,
+    text: &'a str,
+    errors: Vec<ParseError>,
+    /// How many times the parser looked at the token at `steps_pos` without moving past it, to
+    /// catch a grammar function stuck in a loop
+    steps: Cell<u32>,
+    steps_pos: Cell<usize>,
+    /// How deep the next node goes in the tree, see [`MAX_DEPTH`]
+    depth: usize,
+}
+
+/// How deep the tree can get before [`Parser::too_deep`] stops the grammar from going any deeper,
+/// so that deeply nested source can't overflow the stack of the recursive-descent functions
+const MAX_DEPTH: usize = 256;
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(text: &'a str, mut tokens: Vec<Token>) -> Parser<'a> {
+        tokens.retain(|token| token.kind != TokenKind::Eof);
+
+        Parser {
+            builder: TreeBuilder::new(text),
+            tokens,
+            pos: 0,
+            prev_end: 0,
+            text,
+            errors: vec![],
+            steps: Cell::new(0),
+            steps_pos: Cell::new(0),
+            depth: 0,
+        }
+    }
+
+    pub(crate) fn finish(self, lex_errors: Vec<LexError>) -> Parse {
+        Parse {
+            green: self.builder.finish(),
+            errors: self.errors,
+            lex_errors,
+            options: LexerOptions::default(),
+        }
+    }
+
+    /// The kind of the next significant token, `Eof` once there are none left
+    pub(crate) fn current(&self) -> TokenKind {
+        self.nth(0)
+    }
+
+    /// The kind of the significant token `n` tokens after the next one
+    pub(crate) fn nth(&self, n: usize) -> TokenKind {
+        // Unwinding out of nested rules looks at the same token a few times for every level, which
+        // `MAX_DEPTH` keeps well below the limit
+        if self.steps_pos.get() != self.pos {
+            self.steps_pos.set(self.pos);
+            self.steps.set(0);
+        }
+        let steps = self.steps.get();
+        assert!(steps < 10_000, "the parser is stuck");
+        self.steps.set(steps + 1);
+
+        self.nth_token(n).map_or(TokenKind::Eof, |token| token.kind)
+    }
+
+    pub(crate) fn at(&self, kind: TokenKind) -> bool {
+        self.current() == kind
+    }
+
+    pub(crate) fn at_any(&self, kinds: &[TokenKind]) -> bool {
+        kinds.contains(&self.current())
+    }
+
+    /// Adds the next significant token to the tree, along with the trivia before it
+    pub(crate) fn bump(&mut self) {
+        let Some(token) = self.nth_token(0).copied() else {
+            return;
+        };
+        self.bump_as(SyntaxKind::from(token.kind));
+    }
+
+    /// Same as [`Parser::bump`], but adds the token as a different kind
+    pub(crate) fn bump_as(&mut self, kind: SyntaxKind) {
+        self.eat_trivia();
+        if let Some(token) = self.tokens.get(self.pos).copied() {
+            self.builder.token_as(kind, token);
+            self.prev_end = token.span.end;
+            self.pos += 1;
+        }
+    }
+
+    /// Bumps the next token if it is of `kind`
+    pub(crate) fn eat(&mut self, kind: TokenKind) -> bool {
+        if self.at(kind) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Bumps the next token if it is of `kind`, and reports it missing otherwise
+    pub(crate) fn expect(&mut self, kind: TokenKind) -> bool {
+        if self.eat(kind) {
+            return true;
+        }
+        self.error(format!("expected {}", kind));
+        false
+    }
+
+    /// Whether the next token can be an identifier: one, or a contextual keyword used as one
+    pub(crate) fn at_identifier(&self) -> bool {
+        let kind = self.current();
+        kind == TokenKind::Identifier || kind.is_contextual_keyword()
+    }
+
+    /// Bumps an identifier, adding a contextual keyword to the tree as an `Identifier`
+    pub(crate) fn bump_identifier(&mut self) {
+        self.bump_as(SyntaxKind::Identifier);
+    }
+
+    /// Bumps the next token if it can be an identifier, and reports it missing otherwise
+    pub(crate) fn expect_identifier(&mut self) -> bool {
+        if self.at_identifier() {
+            self.bump_identifier();
+            return true;
+        }
+        self.expect(TokenKind::Identifier)
+    }
+
+    /// Bumps a `>` that closes a generic list, splitting it off the front of a `>>`, `>>=` or
+    /// `>=`, which the lexer has no way to tell apart from the operators
+    ///
+    /// The rest of the token stays behind as the next one, so the `>>` of `Vec<Vec<i32>>` closes
+    /// both lists.
+    pub(crate) fn eat_gt(&mut self) -> bool {
+        let rest = match self.current() {
+            TokenKind::OpGt => return self.eat(TokenKind::OpGt),
+            TokenKind::OpBitRshift => TokenKind::OpGt,
+            TokenKind::OpBitRshiftEq => TokenKind::OpGteq,
+            TokenKind::OpGteq => TokenKind::OpEq,
+            _ => return false,
+        };
+
+        self.eat_trivia();
+        let token = &mut self.tokens[self.pos];
+        let gt = Token {
+            kind: TokenKind::OpGt,
+            span: Span {
+                start: token.span.start,
+                end: token.span.start + 1,
+            },
+        };
+        token.kind = rest;
+        token.span.start = gt.span.end;
+
+        self.builder.token(gt);
+        self.prev_end = gt.span.end;
+        // The rest of the token is new, without moving past it
+        self.steps.set(0);
+        true
+    }
+
+    /// The kinds of all the significant tokens from the next one on, for the few rules that have
+    /// to look arbitrarily far ahead
+    pub(crate) fn lookahead(&self) -> impl Iterator<Item = TokenKind> + '_ {
+        self.tokens[self.pos..]
+            .iter()
+            .map(|token| token.kind)
+            .filter(|kind| !is_trivia(*kind))
+    }
+
+    /// Adds the trivia before the next significant token to the tree
+    pub(crate) fn eat_trivia(&mut self) {
+        while let Some(token) = self.tokens.get(self.pos).copied() {
+            if !is_trivia(token.kind) {
+                break;
+            }
+            self.builder.token(token);
+            self.pos += 1;
+        }
+    }
+
+    /// Starts the root node, which unlike any other node also holds the trivia before its first
+    /// token
+    pub(crate) fn start_root(&mut self, kind: SyntaxKind) {
+        self.builder.start_node(kind);
+        self.depth += 1;
+    }
+
+    /// Starts a node at the next significant token
+    ///
+    /// The trivia before it is added first, so nodes never start with whitespace or a comment.
+    pub(crate) fn start_node(&mut self, kind: SyntaxKind) {
+        self.eat_trivia();
+        self.builder.start_node(kind);
+        self.depth += 1;
+    }
+
+    pub(crate) fn finish_node(&mut self) {
+        self.builder.finish_node();
+        self.depth -= 1;
+    }
+
+    /// Marks the next significant token, so a node can be started there once it is clear which
+    /// kind it has to be
+    pub(crate) fn checkpoint(&mut self) -> Checkpoint {
+        self.eat_trivia();
+        self.builder.checkpoint()
+    }
+
+    pub(crate) fn start_node_at(&mut self, checkpoint: Checkpoint, kind: SyntaxKind) {
+        self.builder.start_node_at(checkpoint, kind);
+        self.depth += 1;
+    }
+
+    /// Reports something missing, right after the last token that was fine
+    pub(crate) fn error(&mut self, message: impl Into<String>) {
+        let span = Span {
+            start: self.prev_end,
+            end: self.prev_end,
+        };
+        self.errors.push(ParseError::new(message, span));
+    }
+
+    /// Reports the next token as unexpected, and skips it along with everything after it up to
+    /// a token in `recovery`, wrapping all of it in a single [`SyntaxKind::ErrorNode`]
+    ///
+    /// The next token is always skipped, so callers don't get stuck on it. Skipping stops right
+    /// after a `;`, since that ends whatever was broken, and steps over `{ ... }` as a whole so
+    /// the `}` of a skipped block is not mistaken for the end of the one around it.
+    pub(crate) fn err_recover(&mut self, message: impl Into<String>, recovery: &[TokenKind]) {
+        if self.at(TokenKind::Eof) {
+            self.error(message);
+            return;
+        }
+
+        let start = self.current_span().start;
+        self.start_node(SyntaxKind::ErrorNode);
+        loop {
+            let kind = self.current();
+            if kind == TokenKind::BracketLcurly {
+                self.skip_braces();
+            } else {
+                self.bump();
+            }
+
+            if kind == TokenKind::SymSemiColon || self.at(TokenKind::Eof) || self.at_any(recovery) {
+                break;
+            }
+        }
+        self.finish_node();
+
+        let span = Span {
+            start,
+            end: self.prev_end,
+        };
+        self.errors.push(ParseError::new(message, span));
+    }
+
+    /// Whether the tree is too deep for the rule at the next token to go on, in which case it is
+    /// reported and skipped instead
+    ///
+    /// Every rule the grammar can recurse through checks this first. Skipping takes the next
+    /// token and everything after it up to a `;` or `,` outside of the brackets it skipped, or a
+    /// closing bracket of something around it, all wrapped in a single [`SyntaxKind::ErrorNode`].
+    pub(crate) fn too_deep(&mut self) -> bool {
+        if self.depth < MAX_DEPTH {
+            return false;
+        }
+        if self.at(TokenKind::Eof) {
+            self.error("nested too deeply");
+            return true;
+        }
+
+        let start = self.current_span().start;
+        self.start_node(SyntaxKind::ErrorNode);
+        // How many of each kind of bracket were skipped without being closed
+        let mut open = [0usize; 3];
+        loop {
+            if let Some((bracket, opens)) = bracket(self.current()) {
+                if opens {
+                    open[bracket] += 1;
+                } else {
+                    open[bracket] = open[bracket].saturating_sub(1);
+                }
+            }
+            self.bump();
+
+            let next = self.current();
+            let done = match bracket(next) {
+                Some((bracket, false)) => open[bracket] == 0,
+                _ => {
+                    open == [0; 3] && matches!(next, TokenKind::SymSemiColon | TokenKind::SymComma)
+                }
+            };
+            if done || next == TokenKind::Eof {
+                break;
+            }
+        }
+        self.finish_node();
+
+        let span = Span {
+            start,
+            end: self.prev_end,
+        };
+        self.errors.push(ParseError::new("nested too deeply", span));
+        true
+    }
+
+    /// Bumps a `{`, and everything up to and including its matching `}`
+    pub(crate) fn skip_braces(&mut self) {
+        let mut depth = 0usize;
+
+        loop {
+            match self.current() {
+                TokenKind::BracketLcurly => depth += 1,
+                TokenKind::BracketRcurly => depth -= 1,
+                TokenKind::Eof => {
+                    self.error("expected `}`");
+                    return;
+                }
+                _ => {}
+            }
+            self.bump();
+
+            if depth == 0 {
+                return;
+            }
+        }
+    }
+
+    /// The span of the next significant token, empty at the end of the source
+    pub(crate) fn current_span(&self) -> Span {
+        self.nth_token(0).map_or(
+            Span {
+                start: self.text.len(),
+                end: self.text.len(),
+            },
+            |token| token.span,
+        )
+    }
+
+    fn nth_token(&self, n: usize) -> Option<&Token> {
+        self.tokens[self.pos..]
+            .iter()
+            .filter(|token| !is_trivia(token.kind))
+            .nth(n)
+    }
+}
+
+fn is_trivia(kind: TokenKind) -> bool {
+    SyntaxKind::from(kind).is_trivia()
+}
+
+/// Which of `()`, `[]` or `{}` a token is one half of, and whether it is the opening one
+fn bracket(kind: TokenKind) -> Option<(usize, bool)> {
+    match kind {
+        TokenKind::BracketLparent => Some((0, true)),
+        TokenKind::BracketRparent => Some((0, false)),
+        TokenKind::BracketLsquared => Some((1, true)),
+        TokenKind::BracketRsquared => Some((1, false)),
+        TokenKind::BracketLcurly => Some((2, true)),
+        TokenKind::BracketRcurly => Some((2, false)),
+        _ => None,
+    }
+}
+
+/// Renders a tree the way the parser tests spell out what they expect: one node or token per
+/// line, indented by depth, with trivia left out
+#[cfg(test)]
+pub(crate) fn debug_tree(node: &SyntaxNode) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    let mut depth = 0;
+
+    for event in node.preorder_with_tokens() {
+        match event {
+            rowan::WalkEvent::Enter(element) => {
+                if !element.kind().is_trivia() {
+                    let indent = "  ".repeat(depth);
+                    match &element {
+                        rowan::NodeOrToken::Node(node) => {
+                            writeln!(out, "{}{:?}", indent, node.kind()).unwrap();
+                        }
+                        rowan::NodeOrToken::Token(token) => {
+                            writeln!(out, "{}{:?} {:?}", indent, token.kind(), token.text())
+                                .unwrap();
+                        }
+                    }
+                }
+                if element.as_node().is_some() {
+                    depth += 1;
+                }
+            }
+            rowan::WalkEvent::Leave(element) => {
+                if element.as_node().is_some() {
+                    depth -= 1;
+                }
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn keeps_all_text() {
+        let text = "  // leading\n/// docs\npublic func main() i32 { return 0; } /* trailing */\n";
+        let parse = parse(text);
+
+        assert_eq!(parse.syntax_node().text().to_string(), text);
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+    }
+
+    #[test]
+    fn nodes_do_not_start_with_trivia() {
+        let parse = parse("\n\n  func a() {}\n  func b() {}\n");
+        let root = parse.syntax_node();
+
+        for node in root.descendants().skip(1) {
+            let first = node.first_token().unwrap();
+            assert!(!first.kind().is_trivia(), "{:?} starts with trivia", node);
+        }
+    }
+
+    #[test]
+    fn missing_tokens_are_reported_after_the_last_good_one() {
+        let parse = parse("import std::io\nfunc main() {}");
+
+        assert_eq!(parse.errors.len(), 1);
+        assert_eq!(parse.errors[0].message, "expected `;`");
+        assert_eq!(parse.errors[0].span, Span { start: 14, end: 14 });
+    }
+
+    fn error_messages(parse: &Parse) -> Vec<&str> {
+        parse.errors.iter().map(|e| e.message.as_str()).collect()
+    }
+
+    #[test]
+    fn recovers_at_the_next_declaration() {
+        let text = "x = 1; y func f() {}";
+        let parse = parse(text);
+
+        assert_eq!(
+            error_messages(&parse),
+            vec!["expected a declaration", "expected a declaration"]
+        );
+        assert_eq!(parse.errors[0].span, Span { start: 0, end: 6 });
+        assert_eq!(parse.errors[1].span, Span { start: 7, end: 8 });
+
+        let tree = debug_tree(&parse.syntax_node());
+        assert!(tree.starts_with(
+            "SourceFile\n  ErrorNode\n    Identifier \"x\"\n    OpEq \"=\"\n    ValueInteger \"1\"\n    SymSemiColon \";\"\n  ErrorNode\n    Identifier \"y\"\n  FuncDecl\n"
+        ));
+    }
+
+    #[test]
+    fn recovers_at_the_next_statement() {
+        let parse = parse("func f() { let x = ; ) ) ; return 1 }");
+
+        assert_eq!(
+            error_messages(&parse),
+            vec![
+                "expected an expression",
+                "expected a statement",
+                "expected `;`"
+            ]
+        );
+
+        let root = parse.syntax_node();
+        let kinds = root
+            .descendants()
+            .map(|node| node.kind())
+            .collect::<Vec<_>>();
+        assert!(kinds.contains(&SyntaxKind::VarDecl));
+        assert!(kinds.contains(&SyntaxKind::ReturnStmt));
+    }
+
+    #[test]
+    fn skipped_braces_stay_balanced() {
+        let parse = parse("@ { } } func f() {}");
+
+        assert_eq!(
+            error_messages(&parse),
+            vec!["expected a declaration", "expected a declaration"]
+        );
+        let tree = debug_tree(&parse.syntax_node());
+        assert!(tree.contains("  FuncDecl\n"), "{}", tree);
+    }
+
+    #[test]
+    fn half_typed_function() {
+        let parse = parse("func f(a: i32,\nfunc g() {}");
+
+        assert_eq!(error_messages(&parse), vec!["expected `)`", "expected `;`"]);
+        let funcs = parse
+            .syntax_node()
+            .children()
+            .filter(|node| node.kind() == SyntaxKind::FuncDecl)
+            .count();
+        assert_eq!(funcs, 2);
+    }
+
+    #[test]
+    fn unclosed_blocks() {
+        let text = "class A { func f() { if x {";
+        let parse = parse(text);
+
+        assert_eq!(parse.syntax_node().text().to_string(), text);
+        assert_eq!(error_messages(&parse), vec!["expected `}`"; 3]);
+        assert!(parse.errors.iter().all(|e| e.span.start == text.len()));
+    }
+
+    #[test]
+    fn deeply_unclosed_brackets() {
+        for text in [
+            format!("func f() {{ x = {}", "(".repeat(5000)),
+            format!("func f() {}", "{".repeat(5000)),
+            format!("type T = {}", "[".repeat(5000)),
+        ] {
+            let parse = parse(&text);
+            assert_eq!(parse.syntax_node().text().to_string(), text);
+            assert!(error_messages(&parse).contains(&"nested too deeply"));
+        }
+    }
+
+    #[test]
+    fn long_prefix_chains() {
+        for text in [
+            format!("func f() {{ x = {}1; }}", "!".repeat(20000)),
+            format!("type T = {}i32;", "*".repeat(20000)),
+        ] {
+            let parse = parse(&text);
+            assert_eq!(parse.syntax_node().text().to_string(), text);
+            assert_eq!(error_messages(&parse), vec!["nested too deeply"]);
+        }
+    }
+
+    #[test]
+    fn deep_nesting_is_cut_off() {
+        let depth = 100_000;
+        for text in [
+            format!("let x = {}1{};", "(".repeat(depth), ")".repeat(depth)),
+            format!("func f() {}{}", "{".repeat(depth), "}".repeat(depth)),
+            format!("let x = {}1{};", "f(".repeat(depth), ")".repeat(depth)),
+            format!("let x = {}1;", "1 + ".repeat(depth)),
+            format!("let x = f{};", "()".repeat(depth)),
+            format!("let x = a{};", ".b".repeat(depth)),
+        ] {
+            let parse = parse(&text);
+            assert_eq!(parse.syntax_node().text().to_string(), text);
+            assert_eq!(error_messages(&parse), vec!["nested too deeply"]);
+        }
+
+        let text = format!("let x = {}1{};", "(".repeat(50), ")".repeat(50));
+        assert!(parse(&text).errors.is_empty());
+
+        let text = format!("func f() {{ {}}}", "if x {} else ".repeat(depth));
+        let parse = parse(&text);
+        assert_eq!(parse.syntax_node().text().to_string(), text);
+        assert!(error_messages(&parse).contains(&"nested too deeply"));
+    }
+
+    proptest! {
+        #[test]
+        fn any_tokens_make_a_complete_tree(
+            words in prop::collection::vec(
+                prop::sample::select(vec![
+                    "func", "class", "struct", "enum", "interface", "namespace", "type", "import",
+                    "macro", "public", "static", "let", "const", "mut", "if", "else", "while",
+                    "do", "for", "switch", "case", "default", "break", "return", "try", "catch",
+                    "unsafe", "new", "as", "x", "1", "\"s\"", "{", "}", "(", ")", "[", "]", ";",
+                    ",", ":", "::", ".", "=", "+", "-", "!", "<", ">", ">>", ">=", "?", "->", "@",
+                    "\n",
+                ]),
+                0..64,
+            )
+        ) {
+            let text = words.join(" ");
+            let parse = parse(&text);
+            prop_assert_eq!(parse.syntax_node().text().to_string(), text);
+        }
+
+        #[test]
+        fn any_text_makes_a_complete_tree(text in "\\PC{0,64}") {
+            let parse = parse(&text);
+            prop_assert_eq!(parse.syntax_node().text().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn empty_source() {
+        let parse = parse("");
+        assert_eq!(debug_tree(&parse.syntax_node()), "SourceFile\n");
+    }
+}
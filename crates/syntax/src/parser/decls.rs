@@ -0,0 +1,1155 @@
+//! Declarations: everything that can appear at the top level of a file, or in the body of a
+//! class, struct, interface or namespace
+
+use rowan::Checkpoint;
+
+use super::expressions;
+use super::statements;
+use super::types::{self, PathContext};
+use super::Parser;
+use crate::ast::TokenKind;
+use crate::syntax_kind::SyntaxKind;
+
+/// Keywords that can come before a declaration, in any order
+const MODIFIERS: &[TokenKind] = &[
+    TokenKind::KwordPublic,
+    TokenKind::KwordPrivate,
+    TokenKind::KwordStatic,
+    TokenKind::KwordVirtual,
+    TokenKind::KwordOverride,
+    TokenKind::KwordAbstract,
+    TokenKind::KwordFinal,
+    TokenKind::KwordInline,
+    TokenKind::KwordConstexpr,
+];
+
+/// Operators that can be overloaded with `func operator`
+const OVERLOADABLE: &[TokenKind] = &[
+    TokenKind::OpPlus,
+    TokenKind::OpMinus,
+    TokenKind::OpMul,
+    TokenKind::OpDiv,
+    TokenKind::OpMod,
+    TokenKind::OpEqeq,
+    TokenKind::OpNoteq,
+    TokenKind::OpLt,
+    TokenKind::OpGt,
+    TokenKind::OpLteq,
+    TokenKind::OpGteq,
+    TokenKind::OpNot,
+    TokenKind::OpAnd,
+    TokenKind::OpOr,
+    TokenKind::OpBitNot,
+    TokenKind::OpBitAnd,
+    TokenKind::OpBitOr,
+    TokenKind::OpBitXor,
+    TokenKind::OpBitLshift,
+    TokenKind::OpBitRshift,
+    TokenKind::OpEq,
+    TokenKind::OpPluseq,
+    TokenKind::OpMinuseq,
+    TokenKind::OpMuleq,
+    TokenKind::OpDiveq,
+    TokenKind::OpModEq,
+];
+
+pub(super) fn source_file(p: &mut Parser) {
+    p.start_root(SyntaxKind::SourceFile);
+    while !p.at(TokenKind::Eof) {
+        item(p, false);
+    }
+    p.eat_trivia();
+    p.finish_node();
+}
+
+/// Keywords that start a declaration, after its modifiers
+const DECL_START: &[TokenKind] = &[
+    TokenKind::KwordFunc,
+    TokenKind::KwordClass,
+    TokenKind::KwordStruct,
+    TokenKind::KwordEnum,
+    TokenKind::KwordInter,
+    TokenKind::KwordNamespace,
+    TokenKind::KwordTypedef,
+    TokenKind::KwordImport,
+    TokenKind::KwordExtern,
+    TokenKind::KwordMacro,
+];
+
+/// Where to pick up again after a broken declaration
+const ITEM_RECOVERY: &[TokenKind] = &[
+    TokenKind::KwordFunc,
+    TokenKind::KwordClass,
+    TokenKind::KwordStruct,
+    TokenKind::KwordEnum,
+    TokenKind::KwordInter,
+    TokenKind::KwordNamespace,
+    TokenKind::KwordTypedef,
+    TokenKind::KwordImport,
+    TokenKind::KwordExtern,
+    TokenKind::KwordMacro,
+    TokenKind::KwordPublic,
+    TokenKind::KwordPrivate,
+    TokenKind::KwordStatic,
+    TokenKind::KwordVirtual,
+    TokenKind::KwordOverride,
+    TokenKind::KwordAbstract,
+    TokenKind::KwordFinal,
+    TokenKind::KwordInline,
+    TokenKind::KwordConstexpr,
+    TokenKind::KwordLet,
+    TokenKind::KwordConst,
+    TokenKind::KwordMutable,
+    TokenKind::BracketRcurly,
+];
+
+/// Whether the next token starts a declaration, other than a variable declaration
+pub(super) fn at_item_start(p: &Parser) -> bool {
+    p.at_any(DECL_START) || at_modifier(p)
+}
+
+/// Whether the next token is a modifier
+///
+/// The contextual ones only are when a declaration comes after them, otherwise they are names,
+/// as in `final = true;` or `override()`.
+fn at_modifier(p: &Parser) -> bool {
+    let kind = p.current();
+    if !MODIFIERS.contains(&kind) {
+        return false;
+    }
+    if !kind.is_contextual_keyword() {
+        return true;
+    }
+
+    p.lookahead()
+        .find(|kind| !MODIFIERS.contains(kind))
+        .is_some_and(|kind| {
+            DECL_START.contains(&kind)
+                || statements::VAR_DECL_START.contains(&kind)
+                || kind == TokenKind::Identifier
+                || kind.is_contextual_keyword()
+        })
+}
+
+/// A declaration with its modifiers, fields are only allowed in the body of a type
+pub(super) fn item(p: &mut Parser, fields_allowed: bool) {
+    if p.too_deep() {
+        return;
+    }
+    let m = p.checkpoint();
+    let has_modifiers = modifiers(p);
+
+    match p.current() {
+        TokenKind::KwordFunc => func_decl(p, m),
+        TokenKind::KwordClass => class_decl(p, m),
+        TokenKind::KwordStruct => struct_decl(p, m),
+        TokenKind::KwordEnum => enum_decl(p, m),
+        TokenKind::KwordInter => interface_decl(p, m),
+        TokenKind::KwordNamespace => namespace_decl(p, m),
+        TokenKind::KwordTypedef => type_alias(p, m),
+        TokenKind::KwordImport => import_decl(p, m),
+        TokenKind::KwordExtern => extern_decl(p, m),
+        TokenKind::KwordMacro => macro_decl(p, m),
+        kind if statements::VAR_DECL_START.contains(&kind) => statements::var_decl(p, m),
+        _ if fields_allowed && p.at_identifier() => field_decl(p, m),
+        _ if has_modifiers => {
+            // Keep the modifiers together, the token after them is dealt with on its own
+            p.start_node_at(m, SyntaxKind::ErrorNode);
+            p.error("expected a declaration after the modifiers");
+            p.finish_node();
+        }
+        _ => p.err_recover("expected a declaration", ITEM_RECOVERY),
+    }
+}
+
+fn modifiers(p: &mut Parser) -> bool {
+    if !at_modifier(p) {
+        return false;
+    }
+
+    p.start_node(SyntaxKind::ModifierList);
+    while at_modifier(p) {
+        p.bump();
+    }
+    p.finish_node();
+    true
+}
+
+/// `func name<T>(a: T, b: i32) -> T { ... }`, the `->` is optional and declarations without a
+/// body end in `;`
+fn func_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::FuncDecl);
+    p.bump();
+
+    // `func operator()(f: F)` overloads `()`, but `func operator(f: F)` and `func operator()`
+    // are named `operator`
+    let overload = p.at(TokenKind::KwordOperator)
+        && (p.nth(1) != TokenKind::BracketLparent
+            || p.nth(2) == TokenKind::BracketRparent && p.nth(3) == TokenKind::BracketLparent);
+    if overload {
+        operator_name(p);
+    } else {
+        name(p);
+    }
+    if p.at(TokenKind::OpLt) {
+        generic_param_list(p);
+    }
+
+    if p.at(TokenKind::BracketLparent) {
+        param_list(p, true);
+    } else {
+        p.error("expected `(`");
+    }
+
+    if p.at(TokenKind::OpArrow) || types::at_type_start(p) {
+        p.start_node(SyntaxKind::RetType);
+        p.eat(TokenKind::OpArrow);
+        types::type_(p);
+        p.finish_node();
+    }
+
+    if p.at(TokenKind::BracketLcurly) {
+        statements::block(p);
+    } else {
+        p.expect(TokenKind::SymSemiColon);
+    }
+    p.finish_node();
+}
+
+/// `operator +`, `operator []` or `operator ()`
+fn operator_name(p: &mut Parser) {
+    p.start_node(SyntaxKind::Name);
+    p.bump();
+
+    match p.current() {
+        TokenKind::BracketLsquared if p.nth(1) == TokenKind::BracketRsquared => {
+            p.bump();
+            p.bump();
+        }
+        TokenKind::BracketLparent
+            if p.nth(1) == TokenKind::BracketRparent && p.nth(2) == TokenKind::BracketLparent =>
+        {
+            p.bump();
+            p.bump();
+        }
+        kind if OVERLOADABLE.contains(&kind) => p.bump(),
+        _ => p.error("expected an operator to overload"),
+    }
+    p.finish_node();
+}
+
+/// `(a: i32, b: i32 = 0)`, or `(a, b)` for macros which take untyped parameters
+fn param_list(p: &mut Parser, typed: bool) {
+    p.start_node(SyntaxKind::ParamList);
+    p.bump();
+
+    // A declaration keyword means the list was never closed, as in a half typed `func f(a,`
+    while !p.at_any(&[
+        TokenKind::BracketRparent,
+        TokenKind::BracketLcurly,
+        TokenKind::SymSemiColon,
+        TokenKind::Eof,
+    ]) && !at_item_start(p)
+    {
+        if !p.at_identifier() && !p.at(TokenKind::Underscore) {
+            p.err_recover(
+                "expected a parameter",
+                &[
+                    TokenKind::SymComma,
+                    TokenKind::BracketRparent,
+                    TokenKind::BracketLcurly,
+                ],
+            );
+            p.eat(TokenKind::SymComma);
+            continue;
+        }
+
+        p.start_node(SyntaxKind::Param);
+        binding_name(p);
+        if typed && p.expect(TokenKind::SymColon) {
+            types::type_(p);
+        }
+        if p.eat(TokenKind::OpEq) {
+            expressions::expr(p);
+        }
+        p.finish_node();
+
+        if !p.at(TokenKind::BracketRparent) {
+            p.expect(TokenKind::SymComma);
+        }
+    }
+
+    p.expect(TokenKind::BracketRparent);
+    p.finish_node();
+}
+
+/// `class Name<T> extends Base implements A, B { ... }`
+fn class_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::ClassDecl);
+    p.bump();
+    name(p);
+    if p.at(TokenKind::OpLt) {
+        generic_param_list(p);
+    }
+
+    if p.at(TokenKind::KwordExtends) {
+        type_clause(p, SyntaxKind::ExtendsClause);
+    }
+    if p.at(TokenKind::KwordImplements) {
+        type_clause(p, SyntaxKind::ImplementsClause);
+    }
+
+    item_list(p, true);
+    p.finish_node();
+}
+
+/// `interface Name extends A, B { ... }`
+fn interface_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::InterfaceDecl);
+    p.bump();
+    name(p);
+    if p.at(TokenKind::OpLt) {
+        generic_param_list(p);
+    }
+
+    if p.at(TokenKind::KwordExtends) {
+        type_clause(p, SyntaxKind::ExtendsClause);
+    }
+
+    item_list(p, true);
+    p.finish_node();
+}
+
+/// `struct Name { x: i32, y: i32 }`
+fn struct_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::StructDecl);
+    p.bump();
+    name(p);
+    if p.at(TokenKind::OpLt) {
+        generic_param_list(p);
+    }
+    item_list(p, true);
+    p.finish_node();
+}
+
+/// `<T, U>` after the name of a declaration
+fn generic_param_list(p: &mut Parser) {
+    p.start_node(SyntaxKind::GenericParamList);
+    p.bump();
+
+    while p.at_identifier() {
+        p.start_node(SyntaxKind::GenericParam);
+        name(p);
+        p.finish_node();
+        if !p.eat(TokenKind::SymComma) {
+            break;
+        }
+    }
+
+    if !p.eat_gt() {
+        p.error("expected `>`");
+    }
+    p.finish_node();
+}
+
+/// A keyword followed by a comma separated list of types, like `extends A, B`
+fn type_clause(p: &mut Parser, kind: SyntaxKind) {
+    p.start_node(kind);
+    p.bump();
+
+    types::type_(p);
+    while p.eat(TokenKind::SymComma) {
+        types::type_(p);
+    }
+    p.finish_node();
+}
+
+/// `{ ... }` holding declarations
+pub(super) fn item_list(p: &mut Parser, fields_allowed: bool) {
+    p.start_node(SyntaxKind::ItemList);
+
+    if p.expect(TokenKind::BracketLcurly) {
+        while !p.at_any(&[TokenKind::BracketRcurly, TokenKind::Eof]) {
+            item(p, fields_allowed);
+        }
+        p.expect(TokenKind::BracketRcurly);
+    }
+    p.finish_node();
+}
+
+/// `x: i32`, ended by a `,` or `;` unless it is the last one in its body
+fn field_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::FieldDecl);
+    name(p);
+    if p.expect(TokenKind::SymColon) {
+        types::type_(p);
+    }
+
+    if !p.eat(TokenKind::SymComma)
+        && !p.eat(TokenKind::SymSemiColon)
+        && !p.at(TokenKind::BracketRcurly)
+    {
+        p.error("expected `,` or `;`");
+    }
+    p.finish_node();
+}
+
+/// `enum Name { A, B(i32, f64), C = 4 }`
+fn enum_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::EnumDecl);
+    p.bump();
+    name(p);
+    if p.at(TokenKind::OpLt) {
+        generic_param_list(p);
+    }
+
+    p.start_node(SyntaxKind::VariantList);
+    if p.expect(TokenKind::BracketLcurly) {
+        while !p.at_any(&[TokenKind::BracketRcurly, TokenKind::Eof]) {
+            if !p.at_identifier() {
+                p.err_recover(
+                    "expected an enum variant",
+                    &[TokenKind::SymComma, TokenKind::BracketRcurly],
+                );
+                p.eat(TokenKind::SymComma);
+                continue;
+            }
+            enum_variant(p);
+
+            if !p.at(TokenKind::BracketRcurly) {
+                p.expect(TokenKind::SymComma);
+            }
+        }
+        p.expect(TokenKind::BracketRcurly);
+    }
+    p.finish_node();
+
+    p.finish_node();
+}
+
+fn enum_variant(p: &mut Parser) {
+    p.start_node(SyntaxKind::EnumVariant);
+    name(p);
+
+    if p.eat(TokenKind::BracketLparent) {
+        while !p.at_any(&[TokenKind::BracketRparent, TokenKind::Eof]) && types::at_type_start(p) {
+            types::type_(p);
+            if !p.at(TokenKind::BracketRparent) && !p.expect(TokenKind::SymComma) {
+                break;
+            }
+        }
+        p.expect(TokenKind::BracketRparent);
+    }
+    if p.eat(TokenKind::OpEq) {
+        expressions::expr(p);
+    }
+    p.finish_node();
+}
+
+/// `namespace std::io { ... }`
+fn namespace_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::NamespaceDecl);
+    p.bump();
+    types::path(p, PathContext::Module);
+    item_list(p, false);
+    p.finish_node();
+}
+
+/// `type Name = Type;`
+fn type_alias(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::TypeAlias);
+    p.bump();
+    name(p);
+    if p.at(TokenKind::OpLt) {
+        generic_param_list(p);
+    }
+    if p.expect(TokenKind::OpEq) {
+        types::type_(p);
+    }
+    p.expect(TokenKind::SymSemiColon);
+    p.finish_node();
+}
+
+/// `import std::io as io;`
+fn import_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::ImportDecl);
+    p.bump();
+    types::path(p, PathContext::Module);
+
+    if p.at(TokenKind::KwordAs) {
+        p.start_node(SyntaxKind::ImportAlias);
+        p.bump();
+        name(p);
+        p.finish_node();
+    }
+    p.expect(TokenKind::SymSemiColon);
+    p.finish_node();
+}
+
+/// `extern "C" func puts(s: *u8) i32;`, or a whole `extern "C" { ... }` block of declarations
+fn extern_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::ExternDecl);
+    p.bump();
+    p.eat(TokenKind::ValueString);
+
+    if p.at(TokenKind::BracketLcurly) {
+        item_list(p, false);
+    } else {
+        item(p, false);
+    }
+    p.finish_node();
+}
+
+/// `macro name(a, b) { ... }`, the body is kept as plain tokens
+fn macro_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::MacroDecl);
+    p.bump();
+    name(p);
+
+    if p.at(TokenKind::BracketLparent) {
+        param_list(p, false);
+    }
+
+    if p.at(TokenKind::BracketLcurly) {
+        p.start_node(SyntaxKind::TokenTree);
+        p.skip_braces();
+        p.finish_node();
+    } else {
+        p.error("expected `{`");
+    }
+    p.finish_node();
+}
+
+/// The name a variable or parameter binds, which can be `_` to ignore the value
+pub(super) fn binding_name(p: &mut Parser) {
+    if p.at(TokenKind::Underscore) {
+        p.start_node(SyntaxKind::Name);
+        p.bump();
+        p.finish_node();
+    } else {
+        name(p);
+    }
+}
+
+pub(super) fn name(p: &mut Parser) {
+    if p.at_identifier() {
+        p.start_node(SyntaxKind::Name);
+        p.bump_identifier();
+        p.finish_node();
+    } else {
+        p.error("expected a name");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::{debug_tree, parse};
+
+    fn check(text: &str, expected: &str) {
+        let parse = parse(text);
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        assert_eq!(parse.syntax_node().text().to_string(), text);
+        assert_eq!(debug_tree(&parse.syntax_node()), expected);
+    }
+
+    #[test]
+    fn func() {
+        check(
+            "public static func add(a: i32, b: *u8) -> i32 { return a; }",
+            r#"SourceFile
+  FuncDecl
+    ModifierList
+      KwordPublic "public"
+      KwordStatic "static"
+    KwordFunc "func"
+    Name
+      Identifier "add"
+    ParamList
+      BracketLparent "("
+      Param
+        Name
+          Identifier "a"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "i32"
+      SymComma ","
+      Param
+        Name
+          Identifier "b"
+        SymColon ":"
+        PointerType
+          OpMul "*"
+          PathType
+            Path
+              Identifier "u8"
+      BracketRparent ")"
+    RetType
+      OpArrow "->"
+      PathType
+        Path
+          Identifier "i32"
+    Block
+      BracketLcurly "{"
+      ReturnStmt
+        KwordReturn "return"
+        PathExpr
+          Path
+            Identifier "a"
+        SymSemiColon ";"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn func_without_arrow_or_body() {
+        check(
+            "func main() i32;",
+            r#"SourceFile
+  FuncDecl
+    KwordFunc "func"
+    Name
+      Identifier "main"
+    ParamList
+      BracketLparent "("
+      BracketRparent ")"
+    RetType
+      PathType
+        Path
+          Identifier "i32"
+    SymSemiColon ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn operator_overload() {
+        check(
+            "func operator ==(a: A, b: A) bool; func operator [](i: usize) A;",
+            r#"SourceFile
+  FuncDecl
+    KwordFunc "func"
+    Name
+      KwordOperator "operator"
+      OpEqeq "=="
+    ParamList
+      BracketLparent "("
+      Param
+        Name
+          Identifier "a"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "A"
+      SymComma ","
+      Param
+        Name
+          Identifier "b"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "A"
+      BracketRparent ")"
+    RetType
+      PathType
+        Path
+          Identifier "bool"
+    SymSemiColon ";"
+  FuncDecl
+    KwordFunc "func"
+    Name
+      KwordOperator "operator"
+      BracketLsquared "["
+      BracketRsquared "]"
+    ParamList
+      BracketLparent "("
+      Param
+        Name
+          Identifier "i"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "usize"
+      BracketRparent ")"
+    RetType
+      PathType
+        Path
+          Identifier "A"
+    SymSemiColon ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn class() {
+        check(
+            "abstract class Dog extends Animal implements Pet, Named {\n    name: String;\n    public virtual func bark() {}\n}",
+            r#"SourceFile
+  ClassDecl
+    ModifierList
+      KwordAbstract "abstract"
+    KwordClass "class"
+    Name
+      Identifier "Dog"
+    ExtendsClause
+      KwordExtends "extends"
+      PathType
+        Path
+          Identifier "Animal"
+    ImplementsClause
+      KwordImplements "implements"
+      PathType
+        Path
+          Identifier "Pet"
+      SymComma ","
+      PathType
+        Path
+          Identifier "Named"
+    ItemList
+      BracketLcurly "{"
+      FieldDecl
+        Name
+          Identifier "name"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "String"
+        SymSemiColon ";"
+      FuncDecl
+        ModifierList
+          KwordPublic "public"
+          KwordVirtual "virtual"
+        KwordFunc "func"
+        Name
+          Identifier "bark"
+        ParamList
+          BracketLparent "("
+          BracketRparent ")"
+        Block
+          BracketLcurly "{"
+          BracketRcurly "}"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn struct_and_interface() {
+        check(
+            "struct Point { x: f64, y: f64 } interface Shape extends Drawable { func area() f64; }",
+            r#"SourceFile
+  StructDecl
+    KwordStruct "struct"
+    Name
+      Identifier "Point"
+    ItemList
+      BracketLcurly "{"
+      FieldDecl
+        Name
+          Identifier "x"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "f64"
+        SymComma ","
+      FieldDecl
+        Name
+          Identifier "y"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "f64"
+      BracketRcurly "}"
+  InterfaceDecl
+    KwordInter "interface"
+    Name
+      Identifier "Shape"
+    ExtendsClause
+      KwordExtends "extends"
+      PathType
+        Path
+          Identifier "Drawable"
+    ItemList
+      BracketLcurly "{"
+      FuncDecl
+        KwordFunc "func"
+        Name
+          Identifier "area"
+        ParamList
+          BracketLparent "("
+          BracketRparent ")"
+        RetType
+          PathType
+            Path
+              Identifier "f64"
+        SymSemiColon ";"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn enum_() {
+        check(
+            "enum Shape { Circle(f64), Rect(f64, f64), Empty, }",
+            r#"SourceFile
+  EnumDecl
+    KwordEnum "enum"
+    Name
+      Identifier "Shape"
+    VariantList
+      BracketLcurly "{"
+      EnumVariant
+        Name
+          Identifier "Circle"
+        BracketLparent "("
+        PathType
+          Path
+            Identifier "f64"
+        BracketRparent ")"
+      SymComma ","
+      EnumVariant
+        Name
+          Identifier "Rect"
+        BracketLparent "("
+        PathType
+          Path
+            Identifier "f64"
+        SymComma ","
+        PathType
+          Path
+            Identifier "f64"
+        BracketRparent ")"
+      SymComma ","
+      EnumVariant
+        Name
+          Identifier "Empty"
+      SymComma ","
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn default_values() {
+        check(
+            "enum E { A = 1 + 2 } func f(x: i32 = -1);",
+            r#"SourceFile
+  EnumDecl
+    KwordEnum "enum"
+    Name
+      Identifier "E"
+    VariantList
+      BracketLcurly "{"
+      EnumVariant
+        Name
+          Identifier "A"
+        OpEq "="
+        BinaryExpr
+          Literal
+            ValueInteger "1"
+          OpPlus "+"
+          Literal
+            ValueInteger "2"
+      BracketRcurly "}"
+  FuncDecl
+    KwordFunc "func"
+    Name
+      Identifier "f"
+    ParamList
+      BracketLparent "("
+      Param
+        Name
+          Identifier "x"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "i32"
+        OpEq "="
+        PrefixExpr
+          OpMinus "-"
+          Literal
+            ValueInteger "1"
+      BracketRparent ")"
+    SymSemiColon ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn constants_with_modifiers() {
+        check(
+            "public const MAX: i32 = 10;",
+            r#"SourceFile
+  VarDecl
+    ModifierList
+      KwordPublic "public"
+    KwordConst "const"
+    Name
+      Identifier "MAX"
+    SymColon ":"
+    PathType
+      Path
+        Identifier "i32"
+    OpEq "="
+    Literal
+      ValueInteger "10"
+    SymSemiColon ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn namespace_import_and_type_alias() {
+        check(
+            "import std::io as io; namespace app::util { type Bytes = [u8]; }",
+            r#"SourceFile
+  ImportDecl
+    KwordImport "import"
+    Path
+      Identifier "std"
+      SymColcol "::"
+      Identifier "io"
+    ImportAlias
+      KwordAs "as"
+      Name
+        Identifier "io"
+    SymSemiColon ";"
+  NamespaceDecl
+    KwordNamespace "namespace"
+    Path
+      Identifier "app"
+      SymColcol "::"
+      Identifier "util"
+    ItemList
+      BracketLcurly "{"
+      TypeAlias
+        KwordTypedef "type"
+        Name
+          Identifier "Bytes"
+        OpEq "="
+        ArrayType
+          BracketLsquared "["
+          PathType
+            Path
+              Identifier "u8"
+          BracketRsquared "]"
+        SymSemiColon ";"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn macro_() {
+        check(
+            "macro twice(x) { x; x; }",
+            r#"SourceFile
+  MacroDecl
+    KwordMacro "macro"
+    Name
+      Identifier "twice"
+    ParamList
+      BracketLparent "("
+      Param
+        Name
+          Identifier "x"
+      BracketRparent ")"
+    TokenTree
+      BracketLcurly "{"
+      Identifier "x"
+      SymSemiColon ";"
+      Identifier "x"
+      SymSemiColon ";"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn extern_() {
+        check(
+            r#"extern "C" { func puts(s: *const u8) i32; }"#,
+            r#"SourceFile
+  ExternDecl
+    KwordExtern "extern"
+    ValueString "\"C\""
+    ItemList
+      BracketLcurly "{"
+      FuncDecl
+        KwordFunc "func"
+        Name
+          Identifier "puts"
+        ParamList
+          BracketLparent "("
+          Param
+            Name
+              Identifier "s"
+            SymColon ":"
+            PointerType
+              OpMul "*"
+              KwordConst "const"
+              PathType
+                Path
+                  Identifier "u8"
+          BracketRparent ")"
+        RetType
+          PathType
+            Path
+              Identifier "i32"
+        SymSemiColon ";"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn contextual_keywords_as_names() {
+        check(
+            "class C { final: bool; public final func operator(default: i32) { final = default; } }",
+            r#"SourceFile
+  ClassDecl
+    KwordClass "class"
+    Name
+      Identifier "C"
+    ItemList
+      BracketLcurly "{"
+      FieldDecl
+        Name
+          Identifier "final"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "bool"
+        SymSemiColon ";"
+      FuncDecl
+        ModifierList
+          KwordPublic "public"
+          KwordFinal "final"
+        KwordFunc "func"
+        Name
+          Identifier "operator"
+        ParamList
+          BracketLparent "("
+          Param
+            Name
+              Identifier "default"
+            SymColon ":"
+            PathType
+              Path
+                Identifier "i32"
+          BracketRparent ")"
+        Block
+          BracketLcurly "{"
+          ExprStmt
+            BinaryExpr
+              PathExpr
+                Path
+                  Identifier "final"
+              OpEq "="
+              PathExpr
+                Path
+                  Identifier "default"
+            SymSemiColon ";"
+          BracketRcurly "}"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn function_named_operator_without_parameters() {
+        check(
+            "func operator() {}",
+            r#"SourceFile
+  FuncDecl
+    KwordFunc "func"
+    Name
+      Identifier "operator"
+    ParamList
+      BracketLparent "("
+      BracketRparent ")"
+    Block
+      BracketLcurly "{"
+      BracketRcurly "}"
+"#,
+        );
+
+        // Only a second parameter list makes it the call overload
+        let parse = parse("func operator()(f: F) {}");
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        assert!(debug_tree(&parse.syntax_node()).contains(
+            "    Name\n      KwordOperator \"operator\"\n      BracketLparent \"(\"\n      BracketRparent \")\"\n"
+        ));
+    }
+
+    #[test]
+    fn modifiers_without_a_declaration() {
+        let parse = parse("public static 42 func f();");
+        let tree = debug_tree(&parse.syntax_node());
+
+        assert_eq!(
+            parse
+                .errors
+                .iter()
+                .map(|e| e.message.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "expected a declaration after the modifiers",
+                "expected a declaration"
+            ]
+        );
+        assert!(tree.starts_with(
+            "SourceFile\n  ErrorNode\n    ModifierList\n      KwordPublic \"public\"\n      KwordStatic \"static\"\n  ErrorNode\n    ValueInteger \"42\"\n  FuncDecl\n"
+        ));
+    }
+
+    #[test]
+    fn generic_params() {
+        check(
+            "class Box<T> extends Base<T> { value: T; }",
+            r#"SourceFile
+  ClassDecl
+    KwordClass "class"
+    Name
+      Identifier "Box"
+    GenericParamList
+      OpLt "<"
+      GenericParam
+        Name
+          Identifier "T"
+      OpGt ">"
+    ExtendsClause
+      KwordExtends "extends"
+      PathType
+        Path
+          Identifier "Base"
+          GenericArgList
+            OpLt "<"
+            PathType
+              Path
+                Identifier "T"
+            OpGt ">"
+    ItemList
+      BracketLcurly "{"
+      FieldDecl
+        Name
+          Identifier "value"
+        SymColon ":"
+        PathType
+          Path
+            Identifier "T"
+        SymSemiColon ";"
+      BracketRcurly "}"
+"#,
+        );
+
+        let parse = parse("func map<T, U>(v: Vec<T>) Vec<U>;");
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        let tree = debug_tree(&parse.syntax_node());
+        assert!(tree.contains(
+            "    GenericParamList\n      OpLt \"<\"\n      GenericParam\n        Name\n          Identifier \"T\"\n      SymComma \",\"\n      GenericParam\n"
+        ), "{}", tree);
+    }
+}
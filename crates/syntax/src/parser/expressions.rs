@@ -0,0 +1,561 @@
+//! Expressions, parsed with precedence climbing over [`OPERATORS`]
+
+use super::decls;
+use super::types::{self, PathContext};
+use super::Parser;
+use crate::ast::TokenKind;
+use crate::syntax_kind::SyntaxKind;
+
+/// How tightly an operator binds, loosest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Assignment = 1,
+    Ternary,
+    LogicalOr,
+    LogicalAnd,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equality,
+    Comparison,
+    Shift,
+    Sum,
+    Product,
+    Cast,
+    Prefix,
+    Postfix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Assoc {
+    Left,
+    Right,
+}
+
+/// Where an operator goes, relative to its operands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fixity {
+    Prefix,
+    Infix(Assoc),
+    /// Comes after its operand, with something other than an expression after it: the type of
+    /// `as`, the arguments of a call, the index of `[]` or the name after `.`
+    Postfix,
+}
+
+/// Every operator of the expression grammar, loosest first
+///
+/// `::` is not in here: it only ever joins names into a path, and a path is parsed as a single
+/// operand, so it binds tighter than anything in this table.
+const OPERATORS: &[(Precedence, Fixity, &[TokenKind])] = &[
+    (
+        Precedence::Assignment,
+        Fixity::Infix(Assoc::Right),
+        &[
+            TokenKind::OpEq,
+            TokenKind::OpPluseq,
+            TokenKind::OpMinuseq,
+            TokenKind::OpMuleq,
+            TokenKind::OpDiveq,
+            TokenKind::OpModEq,
+            TokenKind::OpBitAndEq,
+            TokenKind::OpBitOrEq,
+            TokenKind::OpBitXorEq,
+            TokenKind::OpBitLshiftEq,
+            TokenKind::OpBitRshiftEq,
+        ],
+    ),
+    (
+        Precedence::Ternary,
+        Fixity::Infix(Assoc::Right),
+        &[TokenKind::SymQuestion],
+    ),
+    (
+        Precedence::LogicalOr,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpOr],
+    ),
+    (
+        Precedence::LogicalAnd,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpAnd],
+    ),
+    (
+        Precedence::BitOr,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpBitOr],
+    ),
+    (
+        Precedence::BitXor,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpBitXor],
+    ),
+    (
+        Precedence::BitAnd,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpBitAnd],
+    ),
+    (
+        Precedence::Equality,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpEqeq, TokenKind::OpNoteq],
+    ),
+    (
+        Precedence::Comparison,
+        Fixity::Infix(Assoc::Left),
+        &[
+            TokenKind::OpLt,
+            TokenKind::OpGt,
+            TokenKind::OpLteq,
+            TokenKind::OpGteq,
+        ],
+    ),
+    (
+        Precedence::Shift,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpBitLshift, TokenKind::OpBitRshift],
+    ),
+    (
+        Precedence::Sum,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpPlus, TokenKind::OpMinus],
+    ),
+    (
+        Precedence::Product,
+        Fixity::Infix(Assoc::Left),
+        &[TokenKind::OpMul, TokenKind::OpDiv, TokenKind::OpMod],
+    ),
+    (Precedence::Cast, Fixity::Postfix, &[TokenKind::KwordAs]),
+    (
+        Precedence::Prefix,
+        Fixity::Prefix,
+        &[
+            TokenKind::OpNot,
+            TokenKind::OpBitNot,
+            TokenKind::OpMinus,
+            TokenKind::KwordNew,
+        ],
+    ),
+    (
+        Precedence::Postfix,
+        Fixity::Postfix,
+        &[
+            TokenKind::SymDot,
+            TokenKind::BracketLparent,
+            TokenKind::BracketLsquared,
+        ],
+    ),
+];
+
+/// The operator `kind` stands for after an operand, or in front of one if `prefix` is set
+fn operator(kind: TokenKind, prefix: bool) -> Option<(Precedence, Fixity)> {
+    OPERATORS
+        .iter()
+        .find(|(_, fixity, kinds)| (*fixity == Fixity::Prefix) == prefix && kinds.contains(&kind))
+        .map(|(precedence, fixity, _)| (*precedence, *fixity))
+}
+
+/// Binding powers for precedence climbing, an operator only takes operands that bind at least
+/// as tightly as its right binding power
+fn binding_power(precedence: Precedence, assoc: Assoc) -> (u8, u8) {
+    let power = precedence as u8 * 2;
+    match assoc {
+        Assoc::Left => (power, power + 1),
+        Assoc::Right => (power + 1, power),
+    }
+}
+
+const LITERALS: &[TokenKind] = &[
+    TokenKind::ValueInteger,
+    TokenKind::ValueFloat,
+    TokenKind::ValueString,
+    TokenKind::ValueChar,
+    TokenKind::KWordTrue,
+    TokenKind::KWordFalse,
+    TokenKind::KwordNull,
+];
+
+pub(super) fn at_expr_start(p: &Parser) -> bool {
+    let kind = p.current();
+    LITERALS.contains(&kind)
+        || operator(kind, true).is_some()
+        || types::PATH_ROOTS.contains(&kind)
+        || p.at_identifier()
+        || kind == TokenKind::BracketLparent
+}
+
+/// Parses an expression, reporting it missing if there is none
+pub(super) fn expr(p: &mut Parser) -> bool {
+    if expr_bp(p, 0) {
+        true
+    } else {
+        p.error("expected an expression");
+        false
+    }
+}
+
+/// Whether the `<` after a name in an expression opens generic arguments, rather than being a
+/// comparison
+///
+/// It does when everything up to its matching `>` can be part of a type, and that `>` is
+/// followed by `(` or `::`, as in `parse<i32>(s)` or `Vec<i32>::empty()`. Where both readings
+/// would make sense the generic one wins, so `a < b > (c)` calls `a<b>` with `c`, and the
+/// comparisons have to be written `(a < b) > (c)`.
+pub(super) fn at_generic_args(p: &Parser) -> bool {
+    if !p.at(TokenKind::OpLt) {
+        return false;
+    }
+
+    let mut depth = 0usize;
+    let mut tokens = p.lookahead();
+    for kind in tokens.by_ref() {
+        match kind {
+            TokenKind::OpLt => depth += 1,
+            TokenKind::OpGt => depth -= 1,
+            // Closing one more list than is open leaves a `>` behind, which is not a `(`
+            TokenKind::OpBitRshift if depth >= 2 => depth -= 2,
+            TokenKind::Identifier
+            | TokenKind::SymColcol
+            | TokenKind::SymComma
+            | TokenKind::OpMul
+            | TokenKind::OpBitAnd
+            | TokenKind::BracketLsquared
+            | TokenKind::BracketRsquared
+            | TokenKind::KwordConst
+            | TokenKind::KwordMutable => {}
+            kind if kind.is_contextual_keyword() => {}
+            _ => return false,
+        }
+
+        if depth == 0 {
+            return matches!(
+                tokens.next(),
+                Some(TokenKind::BracketLparent | TokenKind::SymColcol)
+            );
+        }
+    }
+
+    false
+}
+
+fn expr_bp(p: &mut Parser, min_power: u8) -> bool {
+    if p.too_deep() {
+        return true;
+    }
+    let m = p.checkpoint();
+    if !operand(p) {
+        return false;
+    }
+
+    // Every operator wraps the ones before it, so a long chain of them makes the tree as deep as
+    // nesting does
+    let depth = p.depth;
+    loop {
+        let kind = p.current();
+        let Some((precedence, fixity)) = operator(kind, false) else {
+            break;
+        };
+
+        match fixity {
+            Fixity::Postfix => {
+                if (precedence as u8 * 2) < min_power || p.too_deep() {
+                    break;
+                }
+                postfix(p, m, kind);
+            }
+            Fixity::Infix(assoc) => {
+                let (left, right) = binding_power(precedence, assoc);
+                if left < min_power || p.too_deep() {
+                    break;
+                }
+
+                if kind == TokenKind::SymQuestion {
+                    p.start_node_at(m, SyntaxKind::TernaryExpr);
+                    p.bump();
+                    expr(p);
+                    if p.expect(TokenKind::SymColon) {
+                        rhs(p, right);
+                    }
+                } else {
+                    p.start_node_at(m, SyntaxKind::BinaryExpr);
+                    p.bump();
+                    rhs(p, right);
+                }
+                p.finish_node();
+            }
+            Fixity::Prefix => unreachable!("prefix operators are only looked up before operands"),
+        }
+        p.depth += 1;
+    }
+    p.depth = depth;
+
+    true
+}
+
+fn rhs(p: &mut Parser, min_power: u8) {
+    if !expr_bp(p, min_power) {
+        p.error("expected an expression");
+    }
+}
+
+fn postfix(p: &mut Parser, m: rowan::Checkpoint, kind: TokenKind) {
+    match kind {
+        TokenKind::KwordAs => {
+            p.start_node_at(m, SyntaxKind::CastExpr);
+            p.bump();
+            types::type_(p);
+        }
+        TokenKind::BracketLparent => {
+            p.start_node_at(m, SyntaxKind::CallExpr);
+            arg_list(p);
+        }
+        TokenKind::BracketLsquared => {
+            p.start_node_at(m, SyntaxKind::IndexExpr);
+            p.bump();
+            expr(p);
+            p.expect(TokenKind::BracketRsquared);
+        }
+        TokenKind::SymDot => {
+            p.start_node_at(m, SyntaxKind::MemberExpr);
+            p.bump();
+            decls::name(p);
+        }
+        _ => unreachable!("{:?} is not a postfix operator", kind),
+    }
+    p.finish_node();
+}
+
+/// A literal, path, parenthesized expression or prefix operator with its operand
+fn operand(p: &mut Parser) -> bool {
+    let kind = p.current();
+
+    if LITERALS.contains(&kind) {
+        p.start_node(SyntaxKind::Literal);
+        p.bump();
+        p.finish_node();
+        return true;
+    }
+
+    match kind {
+        _ if p.at_identifier() || types::PATH_ROOTS.contains(&kind) => {
+            p.start_node(SyntaxKind::PathExpr);
+            types::path(p, PathContext::Expr);
+            p.finish_node();
+        }
+        TokenKind::BracketLparent => {
+            p.start_node(SyntaxKind::ParenExpr);
+            p.bump();
+            expr(p);
+            p.expect(TokenKind::BracketRparent);
+            p.finish_node();
+        }
+        TokenKind::KwordNew => {
+            // `new Type(args)`, the operand is a type rather than an expression
+            p.start_node(SyntaxKind::NewExpr);
+            p.bump();
+            types::type_(p);
+            if p.at(TokenKind::BracketLparent) {
+                arg_list(p);
+            }
+            p.finish_node();
+        }
+        _ => {
+            let Some((precedence, Fixity::Prefix)) = operator(kind, true) else {
+                return false;
+            };
+
+            p.start_node(SyntaxKind::PrefixExpr);
+            p.bump();
+            rhs(p, precedence as u8 * 2);
+            p.finish_node();
+        }
+    }
+
+    true
+}
+
+/// `(a, b, c)`
+fn arg_list(p: &mut Parser) {
+    p.start_node(SyntaxKind::ArgList);
+    p.bump();
+
+    while !p.at_any(&[TokenKind::BracketRparent, TokenKind::Eof]) {
+        if !expr(p) {
+            break;
+        }
+        if !p.at(TokenKind::BracketRparent) && !p.expect(TokenKind::SymComma) {
+            break;
+        }
+    }
+
+    p.expect(TokenKind::BracketRparent);
+    p.finish_node();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::syntax_node::SyntaxNode;
+
+    /// Parses `text` as a single expression, and writes it back out with parentheses around
+    /// every operator and its operands
+    fn grouped(text: &str) -> String {
+        let mut p = Parser::new(text, Lexer::new(text).collect());
+        p.start_root(SyntaxKind::SourceFile);
+        expr(&mut p);
+        assert!(p.at(TokenKind::Eof), "not all of {:?} was parsed", text);
+        p.eat_trivia();
+        p.finish_node();
+
+        let parse = p.finish(vec![]);
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        render(&parse.syntax_node())
+    }
+
+    fn render(node: &SyntaxNode) -> String {
+        let parts = node
+            .children_with_tokens()
+            .filter(|element| !element.kind().is_trivia())
+            .map(|element| match element {
+                rowan::NodeOrToken::Node(node) => render(&node),
+                rowan::NodeOrToken::Token(token) => token.text().to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match node.kind() {
+            SyntaxKind::BinaryExpr
+            | SyntaxKind::PrefixExpr
+            | SyntaxKind::TernaryExpr
+            | SyntaxKind::CastExpr
+            | SyntaxKind::CallExpr
+            | SyntaxKind::IndexExpr
+            | SyntaxKind::MemberExpr
+            | SyntaxKind::NewExpr => format!("({})", parts),
+            _ => parts,
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(grouped("1 + 2 * 3"), "(1 + (2 * 3))");
+        assert_eq!(grouped("1 * 2 + 3"), "((1 * 2) + 3)");
+        assert_eq!(grouped("a - b - c"), "((a - b) - c)");
+        assert_eq!(grouped("a / b % c"), "((a / b) % c)");
+        assert_eq!(grouped("(1 + 2) * 3"), "(( (1 + 2) ) * 3)");
+    }
+
+    #[test]
+    fn comparison_and_logic() {
+        assert_eq!(grouped("a < b == c >= d"), "((a < b) == (c >= d))");
+        assert_eq!(grouped("a || b && c || d"), "((a || (b && c)) || d)");
+        assert_eq!(grouped("a == b && c != d"), "((a == b) && (c != d))");
+    }
+
+    #[test]
+    fn bitwise_and_shifts() {
+        assert_eq!(grouped("a | b ^ c & d"), "(a | (b ^ (c & d)))");
+        assert_eq!(grouped("a & b == c"), "(a & (b == c))");
+        assert_eq!(grouped("a >> 1 + b"), "(a >> (1 + b))");
+        assert_eq!(grouped("a << 1 + b"), "(a << (1 + b))");
+        assert_eq!(grouped("a < b >> c"), "(a < (b >> c))");
+    }
+
+    #[test]
+    fn assignment_is_right_associative() {
+        assert_eq!(grouped("a = b = c"), "(a = (b = c))");
+        assert_eq!(grouped("a += b * 2"), "(a += (b * 2))");
+        assert_eq!(grouped("a |= b | c"), "(a |= (b | c))");
+        assert_eq!(grouped("a >>= b ? c : d"), "(a >>= (b ? c : d))");
+    }
+
+    #[test]
+    fn ternary() {
+        assert_eq!(grouped("a ? b : c ? d : e"), "(a ? b : (c ? d : e))");
+        assert_eq!(grouped("a || b ? c = 1 : d"), "((a || b) ? (c = 1) : d)");
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(grouped("-a * b"), "((- a) * b)");
+        assert_eq!(grouped("!a && ~b"), "((! a) && (~ b))");
+        assert_eq!(grouped("- -a"), "(- (- a))");
+        assert_eq!(grouped("!a.b()"), "(! ((a . b) ( )))");
+    }
+
+    #[test]
+    fn postfix() {
+        assert_eq!(grouped("a.b.c"), "((a . b) . c)");
+        assert_eq!(grouped("f(1, x + 2)(3)"), "((f ( 1 , (x + 2) )) ( 3 ))");
+        assert_eq!(grouped("a[i][j + 1]"), "((a [ i ]) [ (j + 1) ])");
+        assert_eq!(grouped("std::io::print(x)"), "(std :: io :: print ( x ))");
+    }
+
+    #[test]
+    fn generic_args() {
+        assert_eq!(grouped("Vec<i32>::empty()"), "(Vec < i32 > :: empty ( ))");
+        assert_eq!(grouped("parse<Vec<u8>>(s)"), "(parse < Vec < u8 > > ( s ))");
+        // A `<` ... `>` followed by `(` is always read as generic arguments
+        assert_eq!(grouped("a < b > (c)"), "(a < b > ( c ))");
+        assert_eq!(grouped("f(a < b, c > (d))"), "(f ( (a < b , c > ( d )) ))");
+    }
+
+    #[test]
+    fn comparisons_that_look_generic() {
+        assert_eq!(grouped("a < b > c"), "((a < b) > c)");
+        assert_eq!(grouped("(a < b) > (c)"), "(( (a < b) ) > ( c ))");
+        assert_eq!(grouped("a < b && c > (d)"), "((a < b) && (c > ( d )))");
+        assert_eq!(grouped("a < b >> (c)"), "(a < (b >> ( c )))");
+        assert_eq!(grouped("a < 1 > (c)"), "((a < 1) > ( c ))");
+    }
+
+    #[test]
+    fn casts() {
+        assert_eq!(grouped("a * b as i64"), "(a * (b as i64))");
+        assert_eq!(grouped("-a as u8"), "((- a) as u8)");
+        assert_eq!(grouped("a as i32 as f64"), "((a as i32) as f64)");
+        assert_eq!(grouped("p as *const u8"), "(p as * const u8)");
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(grouped("new Foo(1, 2).bar"), "((new Foo ( 1 , 2 )) . bar)");
+        assert_eq!(grouped("new std::Vec"), "(new std :: Vec)");
+    }
+
+    #[test]
+    fn this_self_super_and_null() {
+        assert_eq!(grouped("this.x = null"), "((this . x) = null)");
+        assert_eq!(grouped("self.len()"), "((self . len) ( ))");
+        assert_eq!(grouped("super::f(this)"), "(super :: f ( this ))");
+    }
+
+    #[test]
+    fn every_operator_is_in_the_table_once() {
+        let mut seen = vec![];
+        for (_, fixity, kinds) in OPERATORS {
+            for kind in *kinds {
+                let key = (*kind, *fixity == Fixity::Prefix);
+                assert!(!seen.contains(&key), "{:?} is in the table twice", kind);
+                seen.push(key);
+            }
+        }
+        assert!(seen.contains(&(TokenKind::OpMinus, true)));
+        assert!(seen.contains(&(TokenKind::OpMinus, false)));
+    }
+
+    #[test]
+    fn missing_operand() {
+        let text = "a + ";
+        let mut p = Parser::new(text, Lexer::new(text).collect());
+        p.start_root(SyntaxKind::SourceFile);
+        expr(&mut p);
+        p.eat_trivia();
+        p.finish_node();
+
+        let parse = p.finish(vec![]);
+        assert_eq!(parse.errors.len(), 1);
+        assert_eq!(parse.errors[0].message, "expected an expression");
+        assert_eq!(parse.syntax_node().text().to_string(), text);
+    }
+}
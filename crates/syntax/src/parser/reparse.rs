@@ -0,0 +1,443 @@
+//! Reparsing only the `{ ... }` block an edit falls in
+//!
+//! Parsing a block only depends on its own tokens: it starts at its `{` whatever came before,
+//! and ends at its matching `}` whatever comes after. So when an edit stays inside the braces of a
+//! block, and the new text of the block still lexes to braces that match up the same way, the
+//! block can be parsed on its own and put in place of the old one, keeping the green nodes of
+//! everything around it. Anything else falls back to parsing the whole text again.
+
+use rowan::{GreenNode, TextRange, TextSize};
+
+use super::{decls, parse_with_options, statements, Parse, Parser};
+use crate::ast::{Span, TokenKind};
+use crate::error::{LexError, ParseError};
+use crate::lexer::{Lexer, LexerOptions};
+use crate::syntax_kind::SyntaxKind;
+use crate::syntax_node::SyntaxNode;
+use crate::text_edit::TextEdit;
+
+/// A block that was parsed again on its own
+struct Reparsed {
+    /// The new root, with the new block in place of the old one
+    green: GreenNode,
+    /// Where the old block was, in the text before the edit
+    block: Span,
+    /// The errors in the new block, in the text after the edit
+    errors: Vec<ParseError>,
+    lex_errors: Vec<LexError>,
+}
+
+impl Parse {
+    /// The parse of the text after `edit`, reparsing only the block the edit falls in when it
+    /// can
+    ///
+    /// The result is the same as parsing the whole new text with the options this was parsed
+    /// with, errors included.
+    pub fn reparse(&self, edit: &TextEdit) -> Parse {
+        let root = self.syntax_node();
+        // The first line ending of the file decides which ones are mixed, a block can't tell
+        if !self.options.report_mixed_line_endings {
+            if let Some(reparsed) = reparse_block(&root, edit, self.options) {
+                return Parse {
+                    green: reparsed.green,
+                    errors: merge(
+                        &self.errors,
+                        reparsed.errors,
+                        reparsed.block,
+                        edit,
+                        |error| &mut error.span,
+                    ),
+                    lex_errors: merge(
+                        &self.lex_errors,
+                        reparsed.lex_errors,
+                        reparsed.block,
+                        edit,
+                        |error| &mut error.span,
+                    ),
+                    options: self.options,
+                };
+            }
+        }
+
+        parse_with_options(&edit.apply(&root.text().to_string()), self.options)
+    }
+}
+
+/// The tree of the text after `edit`, reparsing only the block the edit falls in when it can
+///
+/// `options` have to be the ones `root` was parsed with.
+pub(crate) fn reparse(root: &SyntaxNode, edit: &TextEdit, options: LexerOptions) -> GreenNode {
+    match reparse_block(root, edit, options) {
+        Some(reparsed) => reparsed.green,
+        None => parse_with_options(&edit.apply(&root.text().to_string()), options).green,
+    }
+}
+
+fn reparse_block(root: &SyntaxNode, edit: &TextEdit, options: LexerOptions) -> Option<Reparsed> {
+    let node = block_around(root, edit)?;
+    let range = node.text_range();
+    let block = Span {
+        start: range.start().into(),
+        end: range.end().into(),
+    };
+
+    let local_edit = TextEdit::new(
+        Span {
+            start: edit.range.start - block.start,
+            end: edit.range.end - block.start,
+        },
+        edit.new_text.as_str(),
+    );
+    let text = local_edit.apply(&node.text().to_string());
+
+    let mut lexer = Lexer::with_options(&text, options);
+    let tokens = (&mut lexer).collect::<Vec<_>>();
+    if !braces_match(tokens.iter().map(|token| token.kind)) {
+        return None;
+    }
+
+    let mut p = Parser::new(&text, tokens);
+    // As deep as the block is in the whole tree, so that it stops nesting at the same places
+    p.depth = node.ancestors().skip(1).count();
+    match node.kind() {
+        SyntaxKind::Block => statements::block(&mut p),
+        SyntaxKind::ItemList => {
+            let fields_allowed = node.parent().is_some_and(|parent| {
+                matches!(
+                    parent.kind(),
+                    SyntaxKind::ClassDecl | SyntaxKind::StructDecl | SyntaxKind::InterfaceDecl
+                )
+            });
+            decls::item_list(&mut p, fields_allowed)
+        }
+        kind => unreachable!("{:?} is not a block", kind),
+    }
+    // The block has to be closed by the last `}`, with nothing left over for the blocks around
+    // it, rather than skip it as part of something broken
+    if !p.at(TokenKind::Eof) {
+        return None;
+    }
+    let parse = p.finish(vec![]);
+    if parse
+        .syntax_node()
+        .last_child_or_token()
+        .map(|child| child.kind())
+        != Some(SyntaxKind::BracketRcurly)
+    {
+        return None;
+    }
+
+    let offset = |span: Span| Span {
+        start: span.start + block.start,
+        end: span.end + block.start,
+    };
+    Some(Reparsed {
+        green: node.replace_with(parse.green),
+        block,
+        errors: parse
+            .errors
+            .into_iter()
+            .map(|error| ParseError {
+                span: offset(error.span),
+                ..error
+            })
+            .collect(),
+        lex_errors: lexer
+            .take_errors()
+            .into_iter()
+            .map(|error| LexError {
+                span: offset(error.span),
+                ..error
+            })
+            .collect(),
+    })
+}
+
+/// The innermost block whose braces are both outside of the edit
+fn block_around(root: &SyntaxNode, edit: &TextEdit) -> Option<SyntaxNode> {
+    let end = u32::try_from(edit.range.end).ok()?;
+    if TextSize::from(end) > root.text_range().end() {
+        return None;
+    }
+    let range = TextRange::new(TextSize::from(edit.range.start as u32), TextSize::from(end));
+
+    let covering = match root.covering_element(range) {
+        rowan::NodeOrToken::Node(node) => node,
+        rowan::NodeOrToken::Token(token) => token.parent()?,
+    };
+    covering.ancestors().find(|node| {
+        let block = node.text_range();
+        matches!(node.kind(), SyntaxKind::Block | SyntaxKind::ItemList)
+            && block.start() < range.start()
+            && range.end() < block.end()
+            // Its own braces, a block that was never closed can end in the `}` of another one
+            && node.first_child_or_token().map(|child| child.kind())
+                == Some(SyntaxKind::BracketLcurly)
+            && node.last_child_or_token().map(|child| child.kind())
+                == Some(SyntaxKind::BracketRcurly)
+    })
+}
+
+/// Whether the first token is a `{` that only the last token before `Eof` closes
+fn braces_match(kinds: impl Iterator<Item = TokenKind>) -> bool {
+    let mut kinds = kinds.filter(|kind| *kind != TokenKind::Eof).peekable();
+    if kinds.next() != Some(TokenKind::BracketLcurly) {
+        return false;
+    }
+
+    let mut depth = 1usize;
+    while let Some(kind) = kinds.next() {
+        match kind {
+            TokenKind::BracketLcurly => depth += 1,
+            TokenKind::BracketRcurly => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return kinds.peek().is_none();
+        }
+    }
+    false
+}
+
+/// The old errors before and after the old block, with the ones of the new block in between
+///
+/// Errors are reported in order, and anything reported inside a block starts after its `{` and
+/// before its `}`, so the old errors split cleanly around it.
+fn merge<E: Clone>(
+    old: &[E],
+    new: Vec<E>,
+    block: Span,
+    edit: &TextEdit,
+    span: fn(&mut E) -> &mut Span,
+) -> Vec<E> {
+    let mut old = old.to_vec();
+    let before = old
+        .iter_mut()
+        .position(|error| span(error).start > block.start)
+        .unwrap_or(old.len());
+    let after = old
+        .iter_mut()
+        .position(|error| span(error).start >= block.end)
+        .map_or(old.len(), |after| after.max(before));
+
+    let mut merged = old[..before].to_vec();
+    merged.extend(new);
+    merged.extend(old.drain(after..).map(|mut error| {
+        let span = span(&mut error);
+        *span = Span {
+            start: edit.shift(span.start),
+            end: edit.shift(span.end),
+        };
+        error
+    }));
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::AstNode;
+    use crate::keywords::Edition;
+    use crate::parser::parse;
+
+    use proptest::prelude::*;
+
+    fn edit(text: &str, old: &str, new: &str) -> TextEdit {
+        let start = text.find(old).unwrap();
+        TextEdit::new(
+            Span {
+                start,
+                end: start + old.len(),
+            },
+            new,
+        )
+    }
+
+    /// Checks that reparsing gives the same as parsing the edited text from scratch
+    fn check(text: &str, edit: &TextEdit) -> bool {
+        check_with_options(text, edit, LexerOptions::default())
+    }
+
+    fn check_with_options(text: &str, edit: &TextEdit, options: LexerOptions) -> bool {
+        let old = parse_with_options(text, options);
+        let reparsed = old.reparse(edit);
+        let full = parse_with_options(&edit.apply(text), options);
+
+        assert_eq!(reparsed.green, full.green, "{:?} with {:?}", text, edit);
+        assert_eq!(reparsed.errors, full.errors, "{:?} with {:?}", text, edit);
+        assert_eq!(
+            reparsed.lex_errors, full.lex_errors,
+            "{:?} with {:?}",
+            text, edit
+        );
+        assert_eq!(
+            old.tree()
+                .reparse(edit, options)
+                .syntax()
+                .green()
+                .into_owned(),
+            full.green
+        );
+
+        reparse_block(&old.syntax_node(), edit, options).is_some()
+    }
+
+    #[test]
+    fn edits_inside_a_block() {
+        let text = "func f() { let a = 1; }\nfunc g() { return 2; }";
+        assert!(check(text, &edit(text, "1", "a + 1")));
+        assert!(check(text, &edit(text, "return 2;", "")));
+        assert!(check(text, &edit(text, "let a", "if (a) { b; } let a")));
+
+        let old = parse(text);
+        let new = old.reparse(&edit(text, "1", "2"));
+        let (old_g, new_g) = (old.syntax_node(), new.syntax_node());
+        let (old_g, new_g) = (
+            old_g.children().nth(1).unwrap(),
+            new_g.children().nth(1).unwrap(),
+        );
+        // Only the block of `f` was built again, `g` is the very same node
+        assert!(std::ptr::eq(&*old_g.green(), &*new_g.green()));
+    }
+
+    #[test]
+    fn edits_inside_a_body() {
+        let text = "class C { x: i32; func f() {} }\nnamespace n { func g(); }";
+        assert!(check(text, &edit(text, "x: i32;", "y: u8, z: u8;")));
+        assert!(check(text, &edit(text, "func g();", "import a;")));
+    }
+
+    #[test]
+    fn errors_around_the_block_are_kept() {
+        let text = "func f( { let a = ; }\nfunc g() { 1 +; }\nlet b = ;";
+        assert!(check(text, &edit(text, "1 +", "1 + 2")));
+        assert!(check(text, &edit(text, "1 +", "0b102 +")));
+    }
+
+    #[test]
+    fn deep_blocks_stop_nesting_where_the_whole_tree_does() {
+        let text = format!("func f() {}x;{}", "{ ".repeat(240), " }".repeat(240));
+        assert!(parse(&text).errors.is_empty());
+        assert!(check(&text, &edit(&text, "x", &"(".repeat(40))));
+        assert!(check(
+            &text,
+            &edit(
+                &text,
+                "x",
+                &format!("{}x{}", "(".repeat(10), ")".repeat(10))
+            )
+        ));
+    }
+
+    #[test]
+    fn edits_that_change_the_braces_parse_everything() {
+        let text = "func f() { let a = 1; }\nfunc g() { return 2; }";
+        assert!(!check(text, &edit(text, "1;", "1; }")));
+        assert!(!check(text, &edit(text, "1;", "{ 1;")));
+        assert!(!check(text, &edit(text, "1;", "/* 1;")));
+        assert!(!check(text, &edit(text, "f() {", "f() ")));
+        assert!(!check(text, &edit(text, "\nfunc", "func")));
+    }
+
+    proptest! {
+        #[test]
+        fn same_as_parsing_everything(
+            items in prop::collection::vec(item(), 0..6),
+            a in any::<prop::sample::Index>(),
+            b in any::<prop::sample::Index>(),
+            new_words in prop::collection::vec(words(), 0..6),
+        ) {
+            let text = items.join("\n");
+            let boundaries = text
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain([text.len()])
+                .collect::<Vec<_>>();
+            let (a, b) = (*a.get(&boundaries), *b.get(&boundaries));
+            let edit = TextEdit::new(
+                Span { start: a.min(b), end: a.max(b) },
+                new_words.join(" "),
+            );
+            check(&text, &edit);
+        }
+
+        #[test]
+        fn same_as_parsing_everything_in_an_older_edition(
+            items in prop::collection::vec(item(), 0..6),
+            a in any::<prop::sample::Index>(),
+            b in any::<prop::sample::Index>(),
+            new_words in prop::collection::vec(
+                prop::sample::select(vec!["null", "in", "yield", "this", "self", "x", ";", "="]),
+                0..6,
+            ),
+        ) {
+            let text = items.join("\n");
+            let boundaries = text
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain([text.len()])
+                .collect::<Vec<_>>();
+            let (a, b) = (*a.get(&boundaries), *b.get(&boundaries));
+            let edit = TextEdit::new(
+                Span { start: a.min(b), end: a.max(b) },
+                new_words.join(" "),
+            );
+            let options = LexerOptions {
+                edition: Edition::Edition2023,
+                ..LexerOptions::default()
+            };
+            check_with_options(&text, &edit, options);
+        }
+    }
+
+    /// Mostly declarations with bodies, so that a lot of edits fall inside a block
+    fn item() -> impl Strategy<Value = String> {
+        (
+            prop::sample::select(vec!["func f() {", "class C {", "namespace n {", "let"]),
+            prop::collection::vec(words(), 0..10),
+        )
+            .prop_map(|(head, body)| format!("{} {} }}", head, body.join(" ")))
+    }
+
+    fn words() -> impl Strategy<Value = &'static str> {
+        prop::sample::select(vec![
+            "func",
+            "class",
+            "struct",
+            "namespace",
+            "let",
+            "if",
+            "else",
+            "while",
+            "return",
+            "switch",
+            "case",
+            "default",
+            "this",
+            "null",
+            "in",
+            "x",
+            "1",
+            "\"s\"",
+            "\"",
+            "/*",
+            "*/",
+            "//",
+            "\n",
+            "{",
+            "}",
+            "{}",
+            "(",
+            ")",
+            ";",
+            ",",
+            ":",
+            "=",
+            "+",
+            "<",
+            ">",
+            ">>",
+            "é",
+        ])
+    }
+}
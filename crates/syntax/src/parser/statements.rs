@@ -0,0 +1,627 @@
+//! Statements, and the blocks that hold them
+
+use rowan::Checkpoint;
+
+use super::decls;
+use super::expressions;
+use super::types;
+use super::Parser;
+use crate::ast::TokenKind;
+use crate::syntax_kind::SyntaxKind;
+
+/// Keywords that start a variable declaration
+pub(super) const VAR_DECL_START: &[TokenKind] = &[
+    TokenKind::KwordLet,
+    TokenKind::KwordConst,
+    TokenKind::KwordMutable,
+];
+
+/// Where to pick up again after a broken statement
+const STMT_RECOVERY: &[TokenKind] = &[
+    TokenKind::KwordIf,
+    TokenKind::KwordWhile,
+    TokenKind::KwordDo,
+    TokenKind::KwordFor,
+    TokenKind::KwordSwitch,
+    TokenKind::KwordBreak,
+    TokenKind::KwordContinue,
+    TokenKind::KwordReturn,
+    TokenKind::KwordThrow,
+    TokenKind::KwordTry,
+    TokenKind::KwordUnsafe,
+    TokenKind::KwordLet,
+    TokenKind::KwordConst,
+    TokenKind::KwordMutable,
+    TokenKind::KwordFunc,
+    TokenKind::KwordClass,
+    TokenKind::KwordStruct,
+    TokenKind::KwordEnum,
+    TokenKind::KwordInter,
+    TokenKind::KwordTypedef,
+    TokenKind::BracketRcurly,
+];
+
+/// `{ ... }` holding statements
+pub(super) fn block(p: &mut Parser) {
+    p.start_node(SyntaxKind::Block);
+
+    if p.expect(TokenKind::BracketLcurly) {
+        while !p.at_any(&[TokenKind::BracketRcurly, TokenKind::Eof]) {
+            statement(p);
+        }
+        p.expect(TokenKind::BracketRcurly);
+    }
+    p.finish_node();
+}
+
+fn statement(p: &mut Parser) {
+    if p.too_deep() {
+        return;
+    }
+    match p.current() {
+        // An empty statement
+        TokenKind::SymSemiColon => p.bump(),
+        TokenKind::BracketLcurly => block(p),
+        TokenKind::KwordIf => if_stmt(p),
+        TokenKind::KwordWhile => while_stmt(p),
+        TokenKind::KwordDo => do_while_stmt(p),
+        TokenKind::KwordFor => for_stmt(p),
+        TokenKind::KwordSwitch => switch_stmt(p),
+        TokenKind::KwordBreak => keyword_stmt(p, SyntaxKind::BreakStmt, false),
+        TokenKind::KwordContinue => keyword_stmt(p, SyntaxKind::ContinueStmt, false),
+        TokenKind::KwordReturn => keyword_stmt(p, SyntaxKind::ReturnStmt, true),
+        TokenKind::KwordThrow => keyword_stmt(p, SyntaxKind::ThrowStmt, true),
+        TokenKind::KwordTry => try_stmt(p),
+        TokenKind::KwordUnsafe => {
+            p.start_node(SyntaxKind::UnsafeBlock);
+            p.bump();
+            block(p);
+            p.finish_node();
+        }
+        kind if VAR_DECL_START.contains(&kind) => {
+            let m = p.checkpoint();
+            var_decl(p, m);
+        }
+        _ if decls::at_item_start(p) => decls::item(p, false),
+        _ => expr_stmt(p),
+    }
+}
+
+/// `let x: i32 = 1;`, `let mut x = 1;`, `const X = 1;` or `mut x = 1;`
+///
+/// Declarations with modifiers, like `static const X = 1;` in a class, pass in a checkpoint from
+/// before the modifiers.
+pub(super) fn var_decl(p: &mut Parser, m: Checkpoint) {
+    p.start_node_at(m, SyntaxKind::VarDecl);
+    if !p.eat(TokenKind::KwordMutable) {
+        p.bump();
+        p.eat(TokenKind::KwordMutable);
+    }
+
+    decls::binding_name(p);
+    if p.eat(TokenKind::SymColon) {
+        types::type_(p);
+    }
+    if p.eat(TokenKind::OpEq) {
+        expressions::expr(p);
+    }
+    p.expect(TokenKind::SymSemiColon);
+    p.finish_node();
+}
+
+/// `if cond { ... } else if cond { ... } else { ... }`
+fn if_stmt(p: &mut Parser) {
+    p.start_node(SyntaxKind::IfStmt);
+    p.bump();
+    expressions::expr(p);
+    block(p);
+
+    if p.eat(TokenKind::KwordElse) {
+        if p.at(TokenKind::KwordIf) {
+            if_stmt(p);
+        } else {
+            block(p);
+        }
+    }
+    p.finish_node();
+}
+
+/// `while cond { ... }`
+fn while_stmt(p: &mut Parser) {
+    p.start_node(SyntaxKind::WhileStmt);
+    p.bump();
+    expressions::expr(p);
+    block(p);
+    p.finish_node();
+}
+
+/// `do { ... } while cond;`
+fn do_while_stmt(p: &mut Parser) {
+    p.start_node(SyntaxKind::DoWhileStmt);
+    p.bump();
+    block(p);
+    if p.expect(TokenKind::KwordWhile) {
+        expressions::expr(p);
+    }
+    p.expect(TokenKind::SymSemiColon);
+    p.finish_node();
+}
+
+/// `for (let i = 0; i < n; i += 1) { ... }`, every part between the parentheses is optional
+fn for_stmt(p: &mut Parser) {
+    p.start_node(SyntaxKind::ForStmt);
+    p.bump();
+
+    if p.expect(TokenKind::BracketLparent) {
+        if p.at_any(VAR_DECL_START) {
+            let m = p.checkpoint();
+            var_decl(p, m);
+        } else if !p.eat(TokenKind::SymSemiColon) {
+            expr_stmt(p);
+        }
+
+        if !p.at(TokenKind::SymSemiColon) {
+            expressions::expr(p);
+        }
+        p.expect(TokenKind::SymSemiColon);
+
+        if !p.at(TokenKind::BracketRparent) {
+            expressions::expr(p);
+        }
+        p.expect(TokenKind::BracketRparent);
+    }
+
+    block(p);
+    p.finish_node();
+}
+
+/// `switch value { case 1, 2: ... default: ... }`
+fn switch_stmt(p: &mut Parser) {
+    p.start_node(SyntaxKind::SwitchStmt);
+    p.bump();
+    expressions::expr(p);
+
+    if p.expect(TokenKind::BracketLcurly) {
+        while !p.at_any(&[TokenKind::BracketRcurly, TokenKind::Eof]) {
+            match p.current() {
+                TokenKind::KwordCase => {
+                    p.start_node(SyntaxKind::CaseClause);
+                    p.bump();
+                    expressions::expr(p);
+                    while p.eat(TokenKind::SymComma) {
+                        expressions::expr(p);
+                    }
+                    p.expect(TokenKind::SymColon);
+                    clause_body(p);
+                    p.finish_node();
+                }
+                TokenKind::KwordDefault => {
+                    p.start_node(SyntaxKind::DefaultClause);
+                    p.bump();
+                    p.expect(TokenKind::SymColon);
+                    clause_body(p);
+                    p.finish_node();
+                }
+                _ => p.err_recover(
+                    "expected `case` or `default`",
+                    &[
+                        TokenKind::KwordCase,
+                        TokenKind::KwordDefault,
+                        TokenKind::BracketRcurly,
+                    ],
+                ),
+            }
+        }
+        p.expect(TokenKind::BracketRcurly);
+    }
+    p.finish_node();
+}
+
+/// The statements of a `case` or `default` clause, up to the next clause
+fn clause_body(p: &mut Parser) {
+    while !p.at_any(&[
+        TokenKind::KwordCase,
+        TokenKind::BracketRcurly,
+        TokenKind::Eof,
+    ]) && !at_default_clause(p)
+    {
+        statement(p);
+    }
+}
+
+/// Whether the next token starts a `default:` clause, rather than being a name as in
+/// `default = 1;`
+fn at_default_clause(p: &Parser) -> bool {
+    p.at(TokenKind::KwordDefault) && p.nth(1) == TokenKind::SymColon
+}
+
+/// `break;`, `continue;`, `return value;` or `throw value;`
+fn keyword_stmt(p: &mut Parser, kind: SyntaxKind, takes_value: bool) {
+    p.start_node(kind);
+    p.bump();
+    if takes_value && !p.at(TokenKind::SymSemiColon) {
+        expressions::expr(p);
+    }
+    p.expect(TokenKind::SymSemiColon);
+    p.finish_node();
+}
+
+/// `try { ... } catch (e: Error) { ... }`, with any number of `catch` clauses
+fn try_stmt(p: &mut Parser) {
+    p.start_node(SyntaxKind::TryStmt);
+    p.bump();
+    block(p);
+
+    if !p.at(TokenKind::KwordCatch) {
+        p.error("expected `catch`");
+    }
+    while p.at(TokenKind::KwordCatch) {
+        p.start_node(SyntaxKind::CatchClause);
+        p.bump();
+
+        if p.eat(TokenKind::BracketLparent) {
+            p.start_node(SyntaxKind::Param);
+            decls::binding_name(p);
+            if p.eat(TokenKind::SymColon) {
+                types::type_(p);
+            }
+            p.finish_node();
+            p.expect(TokenKind::BracketRparent);
+        }
+
+        block(p);
+        p.finish_node();
+    }
+    p.finish_node();
+}
+
+fn expr_stmt(p: &mut Parser) {
+    let m = p.checkpoint();
+    if !expressions::at_expr_start(p) {
+        p.err_recover("expected a statement", STMT_RECOVERY);
+        return;
+    }
+
+    expressions::expr(p);
+    p.start_node_at(m, SyntaxKind::ExprStmt);
+    p.expect(TokenKind::SymSemiColon);
+    p.finish_node();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::{debug_tree, parse};
+
+    /// Parses `body` as the body of a function, and returns the tree of the body
+    fn check(body: &str, expected: &str) {
+        let text = format!("func f() {{\n{}\n}}", body);
+        let parse = parse(&text);
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        assert_eq!(parse.syntax_node().text().to_string(), text);
+
+        let tree = debug_tree(&parse.syntax_node());
+        let block = tree
+            .split_once("    Block\n")
+            .map(|(_, block)| block)
+            .unwrap();
+        let block = block
+            .lines()
+            .map(|line| &line[6..])
+            .filter(|line| *line != "BracketLcurly \"{\"" && *line != "BracketRcurly \"}\"")
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+
+        assert_eq!(block, expected);
+    }
+
+    #[test]
+    fn underscore_bindings() {
+        check(
+            "let _ = f(); try { } catch (_: Error) { }",
+            r#"VarDecl
+  KwordLet "let"
+  Name
+    Underscore "_"
+  OpEq "="
+  CallExpr
+    PathExpr
+      Path
+        Identifier "f"
+    ArgList
+      BracketLparent "("
+      BracketRparent ")"
+  SymSemiColon ";"
+TryStmt
+  KwordTry "try"
+  Block
+    BracketLcurly "{"
+    BracketRcurly "}"
+  CatchClause
+    KwordCatch "catch"
+    BracketLparent "("
+    Param
+      Name
+        Underscore "_"
+      SymColon ":"
+      PathType
+        Path
+          Identifier "Error"
+    BracketRparent ")"
+    Block
+      BracketLcurly "{"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn var_decls() {
+        check(
+            "let x: i32 = 1; let mut y = x; const Z = 2; mut w = 3;",
+            r#"VarDecl
+  KwordLet "let"
+  Name
+    Identifier "x"
+  SymColon ":"
+  PathType
+    Path
+      Identifier "i32"
+  OpEq "="
+  Literal
+    ValueInteger "1"
+  SymSemiColon ";"
+VarDecl
+  KwordLet "let"
+  KwordMutable "mut"
+  Name
+    Identifier "y"
+  OpEq "="
+  PathExpr
+    Path
+      Identifier "x"
+  SymSemiColon ";"
+VarDecl
+  KwordConst "const"
+  Name
+    Identifier "Z"
+  OpEq "="
+  Literal
+    ValueInteger "2"
+  SymSemiColon ";"
+VarDecl
+  KwordMutable "mut"
+  Name
+    Identifier "w"
+  OpEq "="
+  Literal
+    ValueInteger "3"
+  SymSemiColon ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn if_else() {
+        check(
+            "if a { } else if b { return; } else { }",
+            r#"IfStmt
+  KwordIf "if"
+  PathExpr
+    Path
+      Identifier "a"
+  Block
+    BracketLcurly "{"
+    BracketRcurly "}"
+  KwordElse "else"
+  IfStmt
+    KwordIf "if"
+    PathExpr
+      Path
+        Identifier "b"
+    Block
+      BracketLcurly "{"
+      ReturnStmt
+        KwordReturn "return"
+        SymSemiColon ";"
+      BracketRcurly "}"
+    KwordElse "else"
+    Block
+      BracketLcurly "{"
+      BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn loops() {
+        check(
+            "while x { break; } do { continue; } while y; for (let i = 0; i < n; i += 1) { }",
+            r#"WhileStmt
+  KwordWhile "while"
+  PathExpr
+    Path
+      Identifier "x"
+  Block
+    BracketLcurly "{"
+    BreakStmt
+      KwordBreak "break"
+      SymSemiColon ";"
+    BracketRcurly "}"
+DoWhileStmt
+  KwordDo "do"
+  Block
+    BracketLcurly "{"
+    ContinueStmt
+      KwordContinue "continue"
+      SymSemiColon ";"
+    BracketRcurly "}"
+  KwordWhile "while"
+  PathExpr
+    Path
+      Identifier "y"
+  SymSemiColon ";"
+ForStmt
+  KwordFor "for"
+  BracketLparent "("
+  VarDecl
+    KwordLet "let"
+    Name
+      Identifier "i"
+    OpEq "="
+    Literal
+      ValueInteger "0"
+    SymSemiColon ";"
+  BinaryExpr
+    PathExpr
+      Path
+        Identifier "i"
+    OpLt "<"
+    PathExpr
+      Path
+        Identifier "n"
+  SymSemiColon ";"
+  BinaryExpr
+    PathExpr
+      Path
+        Identifier "i"
+    OpPluseq "+="
+    Literal
+      ValueInteger "1"
+  BracketRparent ")"
+  Block
+    BracketLcurly "{"
+    BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn empty_for() {
+        check(
+            "for (;;) { }",
+            r#"ForStmt
+  KwordFor "for"
+  BracketLparent "("
+  SymSemiColon ";"
+  SymSemiColon ";"
+  BracketRparent ")"
+  Block
+    BracketLcurly "{"
+    BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn switch() {
+        check(
+            "switch x { case 1, 2: f(); break; default: }",
+            r#"SwitchStmt
+  KwordSwitch "switch"
+  PathExpr
+    Path
+      Identifier "x"
+  BracketLcurly "{"
+  CaseClause
+    KwordCase "case"
+    Literal
+      ValueInteger "1"
+    SymComma ","
+    Literal
+      ValueInteger "2"
+    SymColon ":"
+    ExprStmt
+      CallExpr
+        PathExpr
+          Path
+            Identifier "f"
+        ArgList
+          BracketLparent "("
+          BracketRparent ")"
+      SymSemiColon ";"
+    BreakStmt
+      KwordBreak "break"
+      SymSemiColon ";"
+  DefaultClause
+    KwordDefault "default"
+    SymColon ":"
+  BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn try_throw_and_unsafe() {
+        check(
+            "try { throw new Error(); } catch (e: Error) { } unsafe { }",
+            r#"TryStmt
+  KwordTry "try"
+  Block
+    BracketLcurly "{"
+    ThrowStmt
+      KwordThrow "throw"
+      NewExpr
+        KwordNew "new"
+        PathType
+          Path
+            Identifier "Error"
+        ArgList
+          BracketLparent "("
+          BracketRparent ")"
+      SymSemiColon ";"
+    BracketRcurly "}"
+  CatchClause
+    KwordCatch "catch"
+    BracketLparent "("
+    Param
+      Name
+        Identifier "e"
+      SymColon ":"
+      PathType
+        Path
+          Identifier "Error"
+    BracketRparent ")"
+    Block
+      BracketLcurly "{"
+      BracketRcurly "}"
+UnsafeBlock
+  KwordUnsafe "unsafe"
+  Block
+    BracketLcurly "{"
+    BracketRcurly "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn nested_declarations() {
+        check(
+            "func g() { } x = g();",
+            r#"FuncDecl
+  KwordFunc "func"
+  Name
+    Identifier "g"
+  ParamList
+    BracketLparent "("
+    BracketRparent ")"
+  Block
+    BracketLcurly "{"
+    BracketRcurly "}"
+ExprStmt
+  BinaryExpr
+    PathExpr
+      Path
+        Identifier "x"
+    OpEq "="
+    CallExpr
+      PathExpr
+        Path
+          Identifier "g"
+      ArgList
+        BracketLparent "("
+        BracketRparent ")"
+  SymSemiColon ";"
+"#,
+        );
+    }
+}
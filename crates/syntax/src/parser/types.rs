@@ -0,0 +1,234 @@
+//! Types, and the `a::b::c` paths they are named by
+
+use super::expressions;
+use super::Parser;
+use crate::ast::TokenKind;
+use crate::syntax_kind::SyntaxKind;
+
+pub(super) fn at_type_start(p: &Parser) -> bool {
+    p.at_identifier()
+        || p.at_any(&[
+            TokenKind::OpMul,
+            TokenKind::OpBitAnd,
+            TokenKind::BracketLsquared,
+        ])
+}
+
+/// `i32`, `std::Vec<T>`, `*const u8`, `&mut T` or `[T]`
+pub(super) fn type_(p: &mut Parser) {
+    if p.too_deep() {
+        return;
+    }
+    match p.current() {
+        _ if p.at_identifier() => {
+            p.start_node(SyntaxKind::PathType);
+            path(p, PathContext::Type);
+            p.finish_node();
+        }
+        TokenKind::OpMul => {
+            p.start_node(SyntaxKind::PointerType);
+            p.bump();
+            if !p.eat(TokenKind::KwordConst) {
+                p.eat(TokenKind::KwordMutable);
+            }
+            type_(p);
+            p.finish_node();
+        }
+        TokenKind::OpBitAnd => {
+            p.start_node(SyntaxKind::RefType);
+            p.bump();
+            p.eat(TokenKind::KwordMutable);
+            type_(p);
+            p.finish_node();
+        }
+        TokenKind::BracketLsquared => {
+            p.start_node(SyntaxKind::ArrayType);
+            p.bump();
+            type_(p);
+            p.expect(TokenKind::BracketRsquared);
+            p.finish_node();
+        }
+        _ => p.error("expected a type"),
+    }
+}
+
+/// Where a path is written, which decides what a `<` after one of its names means
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PathContext {
+    /// Imports and namespaces, which name modules and take no generic arguments
+    Module,
+    /// Types, where a `<` always opens generic arguments
+    Type,
+    /// Expressions, where a `<` can be a comparison too, see [`expressions::at_generic_args`]
+    Expr,
+}
+
+/// Keywords that can start a path in an expression, like `this.x` or `super::f()`
+pub(super) const PATH_ROOTS: &[TokenKind] = &[
+    TokenKind::KwordThis,
+    TokenKind::KwordSelf,
+    TokenKind::KwordSuper,
+];
+
+/// `a::b::c`, any of the names can be followed by generic arguments, like `Map<K, V>::Entry`
+pub(super) fn path(p: &mut Parser, context: PathContext) {
+    let root = context == PathContext::Expr && PATH_ROOTS.contains(&p.current());
+    if !root && !p.at_identifier() {
+        p.error("expected a path");
+        return;
+    }
+
+    p.start_node(SyntaxKind::Path);
+    if root {
+        p.bump();
+    } else {
+        p.bump_identifier();
+    }
+    generic_args(p, context);
+    while p.at(TokenKind::SymColcol) {
+        p.bump();
+        if p.expect_identifier() {
+            generic_args(p, context);
+        }
+    }
+    p.finish_node();
+}
+
+fn generic_args(p: &mut Parser, context: PathContext) {
+    let generic = match context {
+        PathContext::Module => false,
+        PathContext::Type => p.at(TokenKind::OpLt),
+        PathContext::Expr => expressions::at_generic_args(p),
+    };
+    if generic {
+        generic_arg_list(p);
+    }
+}
+
+/// `<A, B>`
+fn generic_arg_list(p: &mut Parser) {
+    p.start_node(SyntaxKind::GenericArgList);
+    p.bump();
+
+    while at_type_start(p) {
+        type_(p);
+        if !p.eat(TokenKind::SymComma) {
+            break;
+        }
+    }
+
+    if !p.eat_gt() {
+        p.error("expected `>`");
+    }
+    p.finish_node();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::{debug_tree, parse};
+
+    #[test]
+    fn pointer_and_reference_types() {
+        let parse = parse("type P = *const &mut std::Vec;");
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        assert_eq!(
+            debug_tree(&parse.syntax_node()),
+            r#"SourceFile
+  TypeAlias
+    KwordTypedef "type"
+    Name
+      Identifier "P"
+    OpEq "="
+    PointerType
+      OpMul "*"
+      KwordConst "const"
+      RefType
+        OpBitAnd "&"
+        KwordMutable "mut"
+        PathType
+          Path
+            Identifier "std"
+            SymColcol "::"
+            Identifier "Vec"
+    SymSemiColon ";"
+"#
+        );
+    }
+
+    #[test]
+    fn nested_generic_args() {
+        let parse = parse("type M = Map<K, Vec<Vec<i32>>>;");
+        assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+        assert_eq!(
+            debug_tree(&parse.syntax_node()),
+            r#"SourceFile
+  TypeAlias
+    KwordTypedef "type"
+    Name
+      Identifier "M"
+    OpEq "="
+    PathType
+      Path
+        Identifier "Map"
+        GenericArgList
+          OpLt "<"
+          PathType
+            Path
+              Identifier "K"
+          SymComma ","
+          PathType
+            Path
+              Identifier "Vec"
+              GenericArgList
+                OpLt "<"
+                PathType
+                  Path
+                    Identifier "Vec"
+                    GenericArgList
+                      OpLt "<"
+                      PathType
+                        Path
+                          Identifier "i32"
+                      OpGt ">"
+                OpGt ">"
+          OpGt ">"
+    SymSemiColon ";"
+"#
+        );
+    }
+
+    #[test]
+    fn splits_tokens_that_close_generic_args() {
+        for (text, rest) in [
+            ("let v: Vec<i32>= x;", "OpEq \"=\""),
+            ("let v: Vec<Vec<i32>>= x;", "OpEq \"=\""),
+            ("let v: Vec<Vec<i32>>=x;", "OpEq \"=\""),
+        ] {
+            let parse = parse(text);
+            assert!(parse.errors.is_empty(), "{}: {:?}", text, parse.errors);
+            assert_eq!(parse.syntax_node().text().to_string(), text);
+
+            let tree = debug_tree(&parse.syntax_node());
+            assert!(tree.contains(rest), "{}", tree);
+            assert!(
+                !tree.contains("OpGteq") && !tree.contains("OpBitRshift"),
+                "{}",
+                tree
+            );
+        }
+    }
+
+    #[test]
+    fn unclosed_generic_args() {
+        let parse = parse("type V = Vec<i32;");
+        assert_eq!(parse.errors.len(), 1);
+        assert_eq!(parse.errors[0].message, "expected `>`");
+    }
+
+    #[test]
+    fn missing_type() {
+        let parse = parse("type P = ;");
+        assert_eq!(parse.errors.len(), 1);
+        assert_eq!(parse.errors[0].message, "expected a type");
+    }
+}
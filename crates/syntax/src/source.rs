@@ -6,10 +6,18 @@
 /// `Source` also provides a pointer to the current position in the source, which is used by the lexer for fast reads
 ///
 /// # Under the hood (of the hood?)
+/// * `source` is the string the `Source` was created from, it keeps the pointers below alive
 /// * `start` is a pointer to the start of the source
 /// * `end` is a pointer to the end of the source
 /// * `ptr` is a pointer to the current position in the source
-pub struct Source {
+///
+/// # Safety
+///
+/// `start <= ptr <= end` always holds, every method that moves `ptr` keeps it in that range.
+/// Reads through `ptr` only happen when `ptr < end`, so they never leave the string.
+pub struct Source<'a> {
+    /// The whole source, which the pointers point into
+    source: &'a str,
     /// Pointer to the start of the source
     pub(crate) start: *const u8,
     /// Pointer to the end of the source
@@ -19,7 +27,7 @@ pub struct Source {
 }
 
 #[allow(dead_code)]
-impl Source {
+impl<'a> Source<'a> {
     /// Creates a new `Source` instance.
     ///
     /// # Parameters
@@ -28,22 +36,18 @@ impl Source {
     ///
     /// # Returns
     ///
-    /// A [`Source`] instance.
-    ///
-    /// # Safety
-    ///
-    /// This function is safe to call as it does not perform any unsafe operations.
-    /// However, the returned [`Source`] instance contains raw pointers that should be handled with care.
-    /// Misuse of these pointers can lead to undefined behavior.
-    pub fn new<'a>(source: &'a str) -> Self {
+    /// A [`Source`] instance, which borrows `source` for as long as it lives.
+    pub fn new(source: &'a str) -> Self {
         // create a pointer to the initial start of the source
         let start = source.as_ptr();
 
         // create a pointer to the end of the source
+        // SAFETY: `start + len` is the one-past-the-end pointer of `source`
         let end = unsafe { start.add(source.len()) };
 
         // Return the `Source` instance
         Source {
+            source,
             start,
             end,
             ptr: start,
@@ -51,40 +55,42 @@ impl Source {
     }
 
     /// Returns the entire source code as a string.
-    ///
-    /// # Safety
-    ///
-    /// This function is unsafe due to unchecked operations. Ensure the following:
-    /// * `Source` instance was created with a valid string.
-    /// * Pointers `start`, `end`, and `ptr` are untouched.
-    /// * Original string that `Source` was created with is intact.
-    ///
-    /// # Returns
-    ///
-    /// A string slice representing the entire source code.
-    pub unsafe fn get_whole_source<'a>(&self) -> &'a str {
-        // Calculate the length of the source
-        let len = self.end as usize - self.start as usize;
-        // Create a slice from the raw parts
-        std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.start, len))
+    pub fn get_whole_source(&self) -> &'a str {
+        self.source
     }
 
     pub fn get_current_pos(&self) -> usize {
         self.ptr as usize - self.start as usize
     }
 
+    /// Moves forward `offset` bytes, stopping at the end of the source
     pub(super) fn advance(&mut self, offset: usize) {
-        // let value = unsafe { *self.start.add(index) };
-        // self.ptr = unsafe { self.start.add(index) };
+        let offset = offset.min(self.len_remaining());
+        // SAFETY: `offset` was clamped to stay inside of (or one past the end of) the source
         self.ptr = unsafe { self.ptr.add(offset) };
     }
 
+    /// Moves forward a single byte, unless already at the end
+    #[inline]
     pub(super) fn advance_ptr(&mut self) {
-        self.ptr = unsafe { self.ptr.add(1) };
+        if self.ptr < self.end {
+            // SAFETY: `ptr < end`, so `ptr + 1 <= end`
+            self.ptr = unsafe { self.ptr.add(1) };
+        }
     }
 
+    /// The byte at the current position, or `0` at the end of the source
+    ///
+    /// The lexer checks [`Source::is_at_end`] before reading, so `0` is never mistaken for a real
+    /// NUL byte there.
+    #[inline]
     pub(super) fn current(&self) -> u8 {
-        unsafe { *self.ptr.as_ref().unwrap() }
+        if self.ptr < self.end {
+            // SAFETY: `ptr < end`, so it points at a byte of the source
+            unsafe { *self.ptr }
+        } else {
+            0
+        }
     }
 
     pub(super) fn current_pos(&self) -> usize {
@@ -95,52 +101,58 @@ impl Source {
         self.ptr >= self.end
     }
 
+    /// The byte after the current one, same as `peek_nth(1)`
+    #[inline]
     pub(super) fn peek(&self) -> Option<u8> {
-        if self.ptr < self.end {
-            let value = unsafe { *self.ptr.offset(1).as_ref().unwrap() };
+        self.peek_nth(1)
+    }
 
-            Some(value)
+    /// The byte `n` bytes after the current one, or [`None`] if that is past the end
+    #[inline]
+    pub(super) fn peek_nth(&self, n: usize) -> Option<u8> {
+        if n < self.len_remaining() {
+            // SAFETY: `ptr + n < end`, so it points at a byte of the source
+            Some(unsafe { *self.ptr.add(n) })
         } else {
             None
         }
     }
 
-    pub(super) fn get_slice<'a>(&self, start: usize, end: usize) -> &'a str {
-        let len = end - start;
-        unsafe {
-            std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.start.add(start), len))
-        }
+    /// A slice of the source between two byte offsets
+    ///
+    /// Panics if the offsets are out of bounds or not on a character boundary
+    pub(super) fn get_slice(&self, start: usize, end: usize) -> &'a str {
+        &self.source[start..end]
+    }
+
+    /// The part of the source that has not been consumed yet
+    pub(super) fn remaining(&self) -> &'a str {
+        &self.source[self.current_pos()..]
     }
 
     /// Get offset of the current position
-    #[allow(dead_code)]
     pub(super) fn offset(&self) -> u32 {
-        self.ptr as u32 - self.start as u32
+        self.current_pos() as u32
     }
 
+    /// Reads the character at the current position and moves past it
     pub(super) fn next_char(&mut self) -> Option<char> {
-        if self.ptr < self.end {
-            let value = unsafe { *self.ptr.as_ref().unwrap() };
-            self.ptr = unsafe { self.ptr.add(1) };
-            Some(value as char)
-        } else {
-            None
-        }
+        let c = self.peek_char()?;
+        self.advance(c.len_utf8());
+        Some(c)
     }
 
     /// Peek at the next character in the Source
-    /// (Will be used later)
-    #[allow(dead_code)]
     pub(super) fn peek_char(&self) -> Option<char> {
-        if self.ptr < self.end {
-            let value = unsafe { *self.ptr.as_ref().unwrap() };
-            Some(value as char)
-        } else {
-            None
-        }
+        self.remaining().chars().next()
+    }
+
+    fn len_remaining(&self) -> usize {
+        self.end as usize - self.ptr as usize
     }
 }
 
+// These tests poke at the pointer handling, run them with `cargo miri test` after changing it
 #[cfg(test)]
 mod test {
     use super::*;
@@ -152,4 +164,69 @@ mod test {
 
         assert_eq!(prgm.as_ptr(), src.ptr);
     }
+
+    #[test]
+    fn peek_stays_in_bounds() {
+        let mut src = Source::new("ab");
+
+        assert_eq!(src.current(), b'a');
+        assert_eq!(src.peek(), Some(b'b'));
+        assert_eq!(src.peek_nth(0), Some(b'a'));
+        assert_eq!(src.peek_nth(2), None);
+        assert_eq!(src.peek_nth(usize::MAX), None);
+
+        src.advance_ptr();
+        assert_eq!(src.current(), b'b');
+        assert_eq!(src.peek(), None);
+
+        src.advance_ptr();
+        assert!(src.is_at_end());
+        assert_eq!(src.current(), 0);
+        assert_eq!(src.peek_nth(0), None);
+    }
+
+    #[test]
+    fn advance_stops_at_the_end() {
+        let mut src = Source::new("abc");
+
+        src.advance(100);
+        assert!(src.is_at_end());
+        assert_eq!(src.get_current_pos(), 3);
+
+        src.advance_ptr();
+        assert_eq!(src.get_current_pos(), 3);
+        assert_eq!(src.remaining(), "");
+    }
+
+    #[test]
+    fn empty_source() {
+        let src = Source::new("");
+
+        assert!(src.is_at_end());
+        assert_eq!(src.current(), 0);
+        assert_eq!(src.peek(), None);
+        assert_eq!(src.peek_char(), None);
+    }
+
+    #[test]
+    fn chars() {
+        let mut src = Source::new("é😀");
+
+        assert_eq!(src.next_char(), Some('é'));
+        assert_eq!(src.peek_char(), Some('😀'));
+        assert_eq!(src.next_char(), Some('😀'));
+        assert_eq!(src.next_char(), None);
+    }
+
+    #[test]
+    fn slices_outlive_the_source() {
+        let text = String::from("hello world");
+
+        let slice = {
+            let src = Source::new(&text);
+            src.get_slice(6, 11)
+        };
+
+        assert_eq!(slice, "world");
+    }
 }
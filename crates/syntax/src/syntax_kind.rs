@@ -0,0 +1,165 @@
+//! Kinds of the nodes and tokens in the concrete syntax tree
+//!
+//! Every [`TokenKind`] has a [`SyntaxKind`] of the same name, followed by the kinds of the nodes
+//! the parser builds out of them.
+
+use crate::ast::TokenKind;
+
+macro_rules! syntax_kinds {
+    (
+        tokens: [$($token:ident),* $(,)?],
+        nodes: [$($node:ident),* $(,)?] $(,)?
+    ) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(u16)]
+        pub enum SyntaxKind {
+            $($token,)*
+            $($node,)*
+            // Not a real kind, only marks how many kinds there are
+            #[doc(hidden)]
+            __Last,
+        }
+
+        impl From<TokenKind> for SyntaxKind {
+            fn from(kind: TokenKind) -> SyntaxKind {
+                match kind {
+                    $(TokenKind::$token => SyntaxKind::$token,)*
+                }
+            }
+        }
+
+        impl SyntaxKind {
+            /// The [`TokenKind`] this kind was made from, [`None`] for node kinds
+            pub fn to_token_kind(self) -> Option<TokenKind> {
+                match self {
+                    $(SyntaxKind::$token => Some(TokenKind::$token),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+syntax_kinds! {
+    tokens: [
+        Identifier, Underscore,
+        Whitespace, Newline,
+        LineComment, BlockComment, DocLineComment, DocBlockComment,
+        ValueInteger, ValueFloat, ValueString, ValueChar,
+        SymAt, SymDot, SymHash, SymComma, SymColon, SymColcol, SymQuestion, SymSemiColon,
+        BracketLcurly, BracketRcurly, BracketLparent, BracketRparent, BracketRsquared,
+        BracketLsquared,
+        OpMul, OpMod, OpDiv, OpPlus, OpMinus, OpMuleq, OpDiveq, OpModEq, OpPluseq, OpMinuseq, OpGt,
+        OpLt, OpArrow, OpEqeq, OpGteq, OpLteq, OpNoteq, OpEq, OpNot, OpAnd, OpOr, OpBitNot,
+        OpBitOr, OpBitAnd, OpBitXor, OpBitOrEq, OpBitRshift, OpBitLshift, OpBitAndEq, OpBitXorEq,
+        OpBitRshiftEq, OpBitLshiftEq,
+        KWordTrue, KWordFalse, KwordIf, KwordLet, KwordNew, KwordThrow, KwordFor, KwordEnum,
+        KwordFunc, KwordOperator, KwordMacro, KwordElse, KwordBreak, KwordConst, KwordSuper,
+        KwordWhile, KwordExtern, KwordVirtual, KwordOverride, KwordClass, KwordAs, KwordImport,
+        KwordUnsafe, KwordConstexpr, KwordTypedef, KwordMutable, KwordDo, KwordNamespace,
+        KwordStruct, KwordPublic, KwordCase, KwordSwitch, KwordStatic, KwordReturn, KwordPrivate,
+        KwordDefault, KwordTry, KwordCatch, KwordContinue, KwordInter, KwordExtends,
+        KwordImplements, KwordAbstract, KwordFinal, KwordInline, KwordDelete, KwordNull, KwordIn,
+        KwordYield, KwordAsync, KwordThis, KwordSelf,
+        Error, Eof, Unknown,
+    ],
+    nodes: [
+        SourceFile,
+        // Tokens the parser could not make sense of
+        ErrorNode,
+
+        // Declarations
+        FuncDecl, ClassDecl, StructDecl, EnumDecl, InterfaceDecl, NamespaceDecl, TypeAlias,
+        ImportDecl, ExternDecl, MacroDecl,
+        // Parts of declarations
+        ModifierList, Name, Path, ParamList, Param, RetType, ItemList, FieldDecl, VariantList,
+        EnumVariant, ExtendsClause, ImplementsClause, ImportAlias, TokenTree, GenericParamList,
+        GenericParam, GenericArgList,
+
+        // Types
+        PathType, PointerType, RefType, ArrayType,
+
+        // Statements
+        Block, IfStmt, WhileStmt, DoWhileStmt, ForStmt, SwitchStmt, BreakStmt, ContinueStmt,
+        ReturnStmt, ThrowStmt, TryStmt, UnsafeBlock, VarDecl, ExprStmt,
+        CaseClause, DefaultClause, CatchClause,
+
+        // Expressions
+        Literal, PathExpr, BinaryExpr, PrefixExpr, TernaryExpr, CallExpr, IndexExpr, MemberExpr,
+        CastExpr, NewExpr, ParenExpr, ArgList,
+    ],
+}
+
+impl SyntaxKind {
+    /// Whitespace and comments, which the parser steps over but keeps in the tree
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Whitespace
+                | SyntaxKind::Newline
+                | SyntaxKind::LineComment
+                | SyntaxKind::BlockComment
+                | SyntaxKind::DocLineComment
+                | SyntaxKind::DocBlockComment
+        )
+    }
+
+    /// Tokens that name a segment of a path, identifiers and the `this`, `self` and `super` an
+    /// expression's path can start with
+    pub fn is_path_segment(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Identifier
+                | SyntaxKind::KwordThis
+                | SyntaxKind::KwordSelf
+                | SyntaxKind::KwordSuper
+        )
+    }
+
+    pub fn is_token(self) -> bool {
+        self.to_token_kind().is_some()
+    }
+}
+
+impl From<SyntaxKind> for rowan::SyntaxKind {
+    fn from(kind: SyntaxKind) -> rowan::SyntaxKind {
+        rowan::SyntaxKind(kind as u16)
+    }
+}
+
+impl From<rowan::SyntaxKind> for SyntaxKind {
+    fn from(raw: rowan::SyntaxKind) -> SyntaxKind {
+        assert!(
+            raw.0 < SyntaxKind::__Last as u16,
+            "invalid syntax kind {}",
+            raw.0
+        );
+        // SAFETY: `SyntaxKind` is `repr(u16)` with no gaps, and `raw` is below `__Last`
+        unsafe { std::mem::transmute::<u16, SyntaxKind>(raw.0) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn raw_round_trip() {
+        for raw in 0..SyntaxKind::__Last as u16 {
+            let kind = SyntaxKind::from(rowan::SyntaxKind(raw));
+            assert_eq!(rowan::SyntaxKind::from(kind).0, raw);
+        }
+    }
+
+    #[test]
+    fn token_kinds_map_back() {
+        for kind in [
+            TokenKind::Identifier,
+            TokenKind::OpBitLshiftEq,
+            TokenKind::Unknown,
+        ] {
+            assert_eq!(SyntaxKind::from(kind).to_token_kind(), Some(kind));
+        }
+        assert_eq!(SyntaxKind::FuncDecl.to_token_kind(), None);
+    }
+}
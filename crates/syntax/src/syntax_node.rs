@@ -0,0 +1,145 @@
+//! The concrete syntax tree, built on rowan
+//!
+//! The tree is lossless: every byte of the source, including whitespace, comments and anything
+//! the lexer or parser choked on, ends up in exactly one token. Printing the root node gives back
+//! the source text.
+
+use rowan::{GreenNode, GreenNodeBuilder};
+
+use crate::ast::Token;
+use crate::error::LexError;
+use crate::lexer::Lexer;
+use crate::syntax_kind::SyntaxKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SnowballLanguage {}
+
+impl rowan::Language for SnowballLanguage {
+    type Kind = SyntaxKind;
+
+    fn kind_from_raw(raw: rowan::SyntaxKind) -> SyntaxKind {
+        raw.into()
+    }
+
+    fn kind_to_raw(kind: SyntaxKind) -> rowan::SyntaxKind {
+        kind.into()
+    }
+}
+
+pub type SyntaxNode = rowan::SyntaxNode<SnowballLanguage>;
+pub type SyntaxToken = rowan::SyntaxToken<SnowballLanguage>;
+pub type SyntaxElement = rowan::SyntaxElement<SnowballLanguage>;
+
+/// Builds a green tree out of the tokens of a source text
+///
+/// This is a thin layer over [`GreenNodeBuilder`] that speaks [`SyntaxKind`] and [`Token`], so
+/// the text of a token is always the slice of the source its span points at.
+pub struct TreeBuilder<'a> {
+    text: &'a str,
+    inner: GreenNodeBuilder<'static>,
+}
+
+impl<'a> TreeBuilder<'a> {
+    pub fn new(text: &'a str) -> TreeBuilder<'a> {
+        TreeBuilder {
+            text,
+            inner: GreenNodeBuilder::new(),
+        }
+    }
+
+    pub fn start_node(&mut self, kind: SyntaxKind) {
+        self.inner.start_node(kind.into());
+    }
+
+    pub fn finish_node(&mut self) {
+        self.inner.finish_node();
+    }
+
+    /// Marks the current position, so a node can be started there later with
+    /// [`TreeBuilder::start_node_at`]
+    pub fn checkpoint(&self) -> rowan::Checkpoint {
+        self.inner.checkpoint()
+    }
+
+    /// Starts a node that wraps everything added since `checkpoint`
+    pub fn start_node_at(&mut self, checkpoint: rowan::Checkpoint, kind: SyntaxKind) {
+        self.inner.start_node_at(checkpoint, kind.into());
+    }
+
+    /// Adds a token, with the text its span points at
+    pub fn token(&mut self, token: Token) {
+        self.token_as(SyntaxKind::from(token.kind), token);
+    }
+
+    /// Adds a token as a different kind than it was lexed as
+    pub fn token_as(&mut self, kind: SyntaxKind, token: Token) {
+        let text = &self.text[token.span.start..token.span.end];
+        self.inner.token(kind.into(), text);
+    }
+
+    pub fn finish(self) -> GreenNode {
+        self.inner.finish()
+    }
+}
+
+/// Lexes `text` into a [`SyntaxKind::SourceFile`] node holding every token as a direct child,
+/// without any structure
+pub fn lossless_tree(text: &str) -> (GreenNode, Vec<LexError>) {
+    let mut lexer = Lexer::new(text);
+    let mut builder = TreeBuilder::new(text);
+
+    builder.start_node(SyntaxKind::SourceFile);
+    for token in &mut lexer {
+        if token.span.start != token.span.end {
+            builder.token(token);
+        }
+    }
+    builder.finish_node();
+
+    (builder.finish(), lexer.take_errors())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lossless_tree_keeps_all_text() {
+        let text = "func main() {\r\n    // hi\n    return \"x\" + 0xFF; \x01\n}";
+        let (green, errors) = lossless_tree(text);
+        let root = SyntaxNode::new_root(green);
+
+        assert_eq!(root.kind(), SyntaxKind::SourceFile);
+        assert_eq!(root.text().to_string(), text);
+        assert_eq!(errors.len(), 1);
+
+        let kinds = root
+            .children_with_tokens()
+            .map(|element| element.kind())
+            .take(6)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                SyntaxKind::KwordFunc,
+                SyntaxKind::Whitespace,
+                SyntaxKind::Identifier,
+                SyntaxKind::BracketLparent,
+                SyntaxKind::BracketRparent,
+                SyntaxKind::Whitespace,
+            ]
+        );
+        assert!(root
+            .children_with_tokens()
+            .any(|element| element.kind() == SyntaxKind::LineComment));
+    }
+
+    #[test]
+    fn empty_source() {
+        let (green, _) = lossless_tree("");
+        let root = SyntaxNode::new_root(green);
+
+        assert_eq!(root.kind(), SyntaxKind::SourceFile);
+        assert_eq!(root.text().to_string(), "");
+    }
+}
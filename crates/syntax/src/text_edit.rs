@@ -0,0 +1,64 @@
+//! Edits to a source text, as an editor sends them on every keystroke
+
+use crate::ast::Span;
+
+/// Replaces the text in `range` with `new_text`, an insertion has an empty range and a deletion
+/// an empty `new_text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The replaced bytes of the old text
+    pub range: Span,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Span, new_text: impl Into<String>) -> TextEdit {
+        TextEdit {
+            range,
+            new_text: new_text.into(),
+        }
+    }
+
+    /// The text after the edit
+    pub fn apply(&self, text: &str) -> String {
+        let mut edited = String::with_capacity(text.len() + self.new_text.len());
+        edited.push_str(&text[..self.range.start]);
+        edited.push_str(&self.new_text);
+        edited.push_str(&text[self.range.end..]);
+        edited
+    }
+
+    /// Where the new text ends, in the text after the edit
+    pub fn new_end(&self) -> usize {
+        self.range.start + self.new_text.len()
+    }
+
+    /// How far the edit moves the text after it, negative if it moves it back
+    pub fn delta(&self) -> isize {
+        self.new_text.len() as isize - (self.range.end - self.range.start) as isize
+    }
+
+    /// Moves an offset after the edit to where it is in the text after the edit
+    pub fn shift(&self, offset: usize) -> usize {
+        debug_assert!(offset >= self.range.end, "{} is not after the edit", offset);
+        offset - self.range.end + self.new_end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apply_and_shift() {
+        let edit = TextEdit::new(Span { start: 4, end: 5 }, "first");
+        assert_eq!(edit.apply("let a = 1;"), "let first = 1;");
+        assert_eq!(edit.delta(), 4);
+        assert_eq!(edit.shift(6), 10);
+
+        let delete = TextEdit::new(Span { start: 0, end: 4 }, "");
+        assert_eq!(delete.apply("let a"), "a");
+        assert_eq!(delete.delta(), -4);
+        assert_eq!(delete.shift(5), 1);
+    }
+}